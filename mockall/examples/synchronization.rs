@@ -4,9 +4,10 @@
 //! When mockall mocks a function or static method, it does so globally. This
 //! can cause hard to debug and non-deterministic failures when one test
 //! overwrites the mock that another test is depending on. The solution to this
-//! is to add some form of synchronization so that tests that depend on a
-//! specific mock will not run in parallel. This is easily achieved using a
-//! Mutex.
+//! is to make sure that tests that depend on a specific mock will not run in
+//! parallel. An exclusive `Context`, created by
+//! `MockThing::one_context_exclusive`, does that without any synchronization of
+//! your own: it holds a per-method lock for as long as it exists.
 #![deny(warnings)]
 
 use mockall_double::double;
@@ -34,19 +35,12 @@ fn main() {
 #[cfg(test)]
 mod test {
     use crate::my_mock::MockThing;
-    use std::sync::Mutex;
-
-    static MTX: Mutex<()> = Mutex::new(());
 
     #[test]
     fn test_1() {
-        // The mutex might be poisoned if another test fails.  But we don't
-        // care, because it doesn't hold any data.  So don't unwrap the Result
-        // object; whether it's poisoned or not, we'll still hold the
-        // MutexGuard.
-        let _m = MTX.lock();
-
-        let ctx = MockThing::one_context();
+        // Creating an exclusive Context waits until no other test holds one
+        // for the same method.
+        let ctx = MockThing::one_context_exclusive();
         ctx.expect().returning(|| 1);
         let expected = 1;
         assert_eq!(expected, MockThing::one())
@@ -54,9 +48,7 @@ mod test {
 
     #[test]
     fn test_2() {
-        let _m = MTX.lock();
-
-        let ctx = MockThing::one_context();
+        let ctx = MockThing::one_context_exclusive();
         ctx.expect().returning(|| 2);
         let expected = 2;
         assert_eq!(expected, MockThing::one())
@@ -32,6 +32,11 @@
 //! * [`Call counts`](#call-counts)
 //! * [`Sequences`](#sequences)
 //! * [`Checkpoints`](#checkpoints)
+//! * [`Call history`](#call-history)
+//! * [`Failure handlers`](#failure-handlers)
+//! * [`Lenient mocks`](#lenient-mocks)
+//! * [`Shared mocks`](#shared-mocks)
+//! * [`Default implementations`](#default-implementations)
 //! * [`Reference arguments`](#reference-arguments)
 //! * [`Reference return values`](#reference-return-values)
 //! * [`impl Trait`](#impl-trait)
@@ -146,6 +151,25 @@
 //! # }
 //! ```
 //!
+//! To return a different value on each call, supply them all at once with
+//! [`returning_values`](examples::__mock_MockFoo_Foo::__foo::Expectation::returning_values).
+//! The expectation will then be called exactly once per value.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self) -> u32;
+//! }
+//!
+//! let mut mock = MockFoo::new();
+//! mock.expect_foo()
+//!     .returning_values([1u32, 2, 3]);
+//! assert_eq!(1, mock.foo());
+//! assert_eq!(2, mock.foo());
+//! assert_eq!(3, mock.foo());
+//! ```
+//!
 //! Mock objects are always `Send`.  If you need to use a return type that
 //! isn't, you can use the
 //! [`return_const_st`](examples::__mock_MockFoo_Foo::__foo::Expectation::return_const_st),
@@ -200,7 +224,14 @@
 //! mock.foo(0);    // Panics!
 //! ```
 //!
+//! The panic message lists every expectation set on the method, and whether
+//! each argument satisfied its predicate.  Predicates are evaluated again to
+//! build that message, using [`CaseTreeExt`] to explain nested failures, but
+//! functions set with `withf` aren't called again.
+//!
 //! See [`predicate`] for a list of Mockall's builtin predicate functions.
+//! When an argument can't be predicted, a [`Captor`] can be used instead.  It
+//! matches anything, and records each argument for the test to inspect later.
 //! For convenience,
 //! [`withf`](examples::__mock_MockFoo_Foo::__foo::Expectation::withf)
 //! is a shorthand for setting the commonly used
@@ -299,6 +330,33 @@
 //! [`never`](examples::__mock_MockFoo_Foo::__foo::Expectation::never) and
 //! [`times`](examples::__mock_MockFoo_Foo::__foo::Expectation::times).
 //!
+//! If the mock is called from another thread, a test can block until an
+//! expectation reaches its minimum call count with [`ExpectationHandle::wait`].
+//! Async tests can instead await the expectation's [`Completion`].
+//!
+//! When a method has many expectations, it can be hard to tell which one
+//! failed.  Label them with
+//! [`named`](examples::__mock_MockFoo_Foo::__foo::Expectation::named), and the
+//! label will be included in every failure message about that expectation.
+//! Those messages also give the file and line where the expectation was
+//! created.
+//!
+//! ```should_panic(expected = "named \"retry after timeout\"")
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self, x: u32);
+//! }
+//!
+//! let mut mock = MockFoo::new();
+//! mock.expect_foo()
+//!     .withf(|x| *x > 5)
+//!     .times(1)
+//!     .named("retry after timeout")
+//!     .return_const(());
+//! // Panics when mock is dropped, because foo wasn't called
+//! ```
+//!
 //! ## Sequences
 //!
 //! By default expectations may be matched in any order.  But it's possible to
@@ -331,6 +389,19 @@
 //! # }
 //! ```
 //!
+//! Expectations in a sequence may also have a range of call counts.  Such an
+//! expectation may keep being called until the next one in the sequence is,
+//! and may be skipped entirely if its minimum is zero.  See [`Sequence`] for
+//! an example.  That includes an expectation whose call count was never set,
+//! because by default any number of calls are allowed.  Set an exact count,
+//! like `.times(1)`, to make a step mandatory.
+//!
+//! An expectation may join several sequences, by calling `in_sequence` more
+//! than once.  For orderings that a sequence can't express, an expectation
+//! can instead be required to happen `after` another, identified by an
+//! [`ExpectationHandle`].  That allows partial orders, like "`a` and `b` in
+//! either order, but both before `c`".
+//!
 //! ## Checkpoints
 //!
 //! Sometimes its useful to validate all expectations mid-test, throw them away,
@@ -375,6 +446,159 @@
 //! # }
 //! ```
 //!
+//! ## Call history
+//!
+//! Every mock method records the arguments of each call, whether or not an
+//! expectation matched it.  They can be retrieved in call order with the mock
+//! object's `calls_*` methods, or with a static method's `Context::calls`.
+//! Each argument is returned as an `Option` of its owned type, which will be
+//! `None` if the argument isn't `Clone + Send + 'static`.  A checkpoint clears
+//! the call history along with the expectations.
+//!
+//! The recorded arguments are clones, and they're kept until the next
+//! checkpoint.  So an argument like a channel's `Sender` stays alive until then,
+//! even after the call returns.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self, x: u32, s: &str);
+//! }
+//!
+//! let mut mock = MockFoo::new();
+//! mock.expect_foo()
+//!     .return_const(());
+//!
+//! mock.foo(1, "one");
+//! mock.foo(2, "two");
+//! assert_eq!(mock.calls_foo(), [
+//!     (Some(1), Some(String::from("one"))),
+//!     (Some(2), Some(String::from("two")))
+//! ]);
+//! ```
+//!
+//! ## Failure handlers
+//!
+//! By default, Mockall panics as soon as a mock is misused.  That isn't
+//! always convenient, for example when a test wants to report every failure
+//! rather than just the first, or when a test harness has its own way of
+//! recording errors.  [`set_failure_handler`] installs a [`FailureHandler`]
+//! for the current thread.  It receives each [`Failure`], which holds a
+//! [`FailureKind`] and the message that Mockall would otherwise have
+//! panicked with.  Where it can, the mock carries on after the handler
+//! returns.  But an unexpected call or a missing return value leaves the mock
+//! with nothing to return, so those will still panic afterwards.  Dropping
+//! the returned guard restores the previous handler.
+//!
+//! ## Lenient mocks
+//!
+//! By default, calling a mock method that doesn't match any expectation will
+//! panic.  Every non-static method also has a fallback expectation, accessed
+//! with the `fallback_*` methods, that's used only when no other expectation
+//! matches.  And a mock object created with `new_lenient` will start with
+//! fallbacks that return `Default::default()`.  Types that don't implement
+//! `Default` can still get their default values from a custom fallback.  Like
+//! other expectations, fallbacks are discarded by a checkpoint, but a lenient
+//! mock stays lenient.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self, x: u32) -> u32;
+//!     fn bar(&self) -> Option<String>;
+//! }
+//!
+//! let mut mock = MockFoo::new_lenient();
+//! mock.expect_foo()
+//!     .with(predicate::eq(4))
+//!     .return_const(42u32);
+//! mock.fallback_foo()
+//!     .returning(|x| x + 1);
+//!
+//! assert_eq!(42, mock.foo(4));
+//! assert_eq!(6, mock.foo(5));
+//! assert_eq!(None, mock.bar());
+//! ```
+//!
+//! Static methods are never lenient.  And a lenient generic method can only
+//! return a default reference for type parameters that already have an
+//! expectation.  If a generic method's return type isn't bound by `Default`,
+//! returning a default value requires the "nightly" feature.
+//!
+//! ## Shared mocks
+//!
+//! Once a mock object has been moved into the code under test, for example as
+//! a `Box<dyn Trait>` or a struct field, the test can't reach it anymore.  To
+//! keep configuring it, create it with `MockFoo::shared()` instead of
+//! `MockFoo::new()`, or convert an existing one with `SharedMockFoo::from`.
+//! That returns a `SharedMockFoo`: a cheaply cloneable handle that implements
+//! the same traits, forwarding each call to the one underlying mock object.
+//! Give one clone to the code under test, and keep another to set
+//! expectations or to checkpoint through its `lock` method.  Expectations are
+//! verified when the last handle is dropped.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo(&self, x: u32) -> u32;
+//! }
+//!
+//! struct Widget(Box<dyn Foo>);
+//!
+//! let handle = MockFoo::shared();
+//! let widget = Widget(Box::new(handle.clone()));
+//! handle.lock()
+//!     .expect_foo()
+//!     .return_const(1u32);
+//! assert_eq!(1, widget.0.foo(0));
+//! handle.lock().checkpoint();
+//! handle.lock()
+//!     .expect_foo()
+//!     .return_const(2u32);
+//! assert_eq!(2, widget.0.foo(0));
+//! ```
+//!
+//! Calls through a handle lock the mock object, so an expectation's closure
+//! must not call the same mock, and the test must not hold the lock while the
+//! code under test runs.  The handle only implements the traits whose every
+//! method it can forward.  It can't forward methods that take `self` by value,
+//! methods that return a reference, `impl Trait`, or `Self`, or async methods.
+//! The handle's documentation lists any traits that it doesn't implement.  A
+//! mock's inherent methods, and the methods of such traits, can still be
+//! called through `lock`.
+//!
+//! ## Default implementations
+//!
+//! When `#[automock]` mocks a trait method that has a default implementation,
+//! an expectation can call that implementation instead of returning a value.
+//! That way a test can mock only a trait's required methods, and keep the
+//! provided ones real.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn required(&self, x: u32) -> u32;
+//!     fn provided(&self, x: u32) -> u32 {
+//!         self.required(x) + 1
+//!     }
+//! }
+//!
+//! let mut mock = MockFoo::new();
+//! mock.expect_required()
+//!     .return_const(41u32);
+//! mock.expect_provided()
+//!     .call_default();
+//! assert_eq!(42, mock.provided(5));
+//! ```
+//!
+//! `call_default` isn't available for `async` methods, methods that return
+//! `impl Trait`, methods with closure arguments, or methods mocked with
+//! [`mock!`].
+//!
 //! ## Reference arguments
 //!
 //! Mockall can mock methods with reference arguments, too.  There's one catch:
@@ -623,6 +847,32 @@
 //! # }
 //! ```
 //!
+//! ### Returning closures
+//!
+//! A method returning `impl Fn`, `Box<dyn Fn>`, or an associated type that is
+//! a boxed closure works the same way.  Just return a boxed closure from
+//! `returning`.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock(type Callback = Box<dyn Fn(u32) -> u32 + Send>;)]
+//! trait Registry {
+//!     type Callback;
+//!     fn get(&self, name: &str) -> Self::Callback;
+//!     fn adder(&self, x: u32) -> impl Fn(u32) -> u32;
+//! }
+//!
+//! # fn main() {
+//! let mut mock = MockRegistry::new();
+//! mock.expect_get()
+//!     .returning(|_| Box::new(|x| x + 1));
+//! mock.expect_adder()
+//!     .returning(|y| Box::new(move |x| x + y));
+//! assert_eq!(2, mock.get("inc")(1));
+//! assert_eq!(7, mock.adder(5)(2));
+//! # }
+//! ```
+//!
 //! ## Mocking structs
 //!
 //! Mockall mocks structs as well as traits.  The problem here is a namespace
@@ -670,6 +920,32 @@
 //! For structs with more than one `impl` block or that have unsupported
 //! `#[derive(X)]` attributes, e.g. `Clone`, see [`mock!`] instead.
 //!
+//! The `impl` block may also name its struct by a path, like `impl
+//! crate::thing::Thing`.  The mock struct is always created alongside the
+//! `impl` block, not alongside the original struct, and it's named after only
+//! the last segment of the path.  The `impl` block's method signatures are
+//! already written relative to its own module, so they're used unchanged.
+//! That means that two such `impl` blocks in the same module can't name
+//! structs that have the same name, even if they're in different modules.
+//!
+//! ```compile_fail
+//! # use mockall::*;
+//! mod a { pub struct Foo; }
+//! mod b { pub struct Foo; }
+//!
+//! #[automock]
+//! impl a::Foo {
+//!     pub fn foo(&self) -> u32 { 0 }
+//! }
+//! // error: `__mockall_MockFoo_is_already_defined_here` is defined multiple
+//! // times
+//! #[automock]
+//! impl b::Foo {
+//!     pub fn bar(&self) -> u32 { 0 }
+//! }
+//! # fn main() {}
+//! ```
+//!
 //! ## Generic methods
 //!
 //! Mocking generic methods is possible, but the exact process depends on
@@ -699,6 +975,21 @@
 //! assert_eq!(-5, mock.foo(5i8));
 //! ```
 //!
+//! Const generic parameters work the same way.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock]
+//! trait Foo {
+//!     fn foo<const N: usize>(&self, buf: [u8; N]) -> usize;
+//! }
+//!
+//! let mut mock = MockFoo::new();
+//! mock.expect_foo::<4>()
+//!     .return_const(4usize);
+//! assert_eq!(4, mock.foo([0u8; 4]));
+//! ```
+//!
 //! ### With non-`static` type parameters
 //!
 //! Mocking methods with non-`'static` type parameters is harder.  The way
@@ -740,9 +1031,43 @@
 //! ## Generic traits and structs
 //!
 //! Mocking generic structs and generic traits is not a problem.  The mock
-//! struct will be generic, too.  As with generic methods, lifetime parameters
-//! are not allowed.  However, as long as the generic parameters are not used by
-//! any static methods, then the parameters need not be `'static'`.
+//! struct will be generic, too.  As long as the generic parameters are not used
+//! by any static methods, then the parameters need not be `'static'`.  Const
+//! generic parameters are allowed, too.
+//!
+//! Lifetime parameters, with or without bounds, are allowed as well.  The mock
+//! struct will have the same lifetime parameters, but its expectations will
+//! not.  So any value returned with one of those lifetimes must be `'static`,
+//! and any argument with one of those lifetimes is only borrowed for the
+//! duration of the call.
+//!
+//! ```
+//! # use mockall::*;
+//! pub struct Parser<'a> {
+//!     input: &'a str
+//! }
+//! #[automock]
+//! impl<'a> Parser<'a> {
+//!     pub fn peek(&self) -> &'a str {
+//!         self.input
+//!     }
+//!     pub fn feed(&mut self, input: &'a str) {
+//!         self.input = input;
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let mut mock = MockParser::default();
+//! mock.expect_feed()
+//!     .withf(|input| input.starts_with('x'))
+//!     .return_const(());
+//! mock.expect_peek()
+//!     .return_const("xyz");
+//! let s = String::from("xyz");
+//! mock.feed(&s);
+//! assert_eq!("xyz", mock.peek());
+//! # }
+//! ```
 //!
 //! ```
 //! # use mockall::*;
@@ -842,16 +1167,56 @@
 //! # }
 //! ```
 //!
+//! Mocks of some commonly used `std` traits, like `Read`, `Write`, and
+//! `Iterator`, are already available in the
+//! [`std_mocks`](std_mocks/index.html) module, with the **std_mocks** feature.
+//!
 //! ## Static methods
 //!
-//! Mockall can also mock static methods.  But be careful!  The expectations are
-//! global.  If you want to use a static method in multiple tests, you must
-//! provide your own synchronization. See the [`synchronization
-//! example`](https://github.com/asomers/mockall/blob/master/mockall/examples/synchronization.rs)
-//! for a basic implementation. For ordinary methods, expectations are
-//! set on the mock object.  But static methods don't have any mock object.
+//! Mockall can also mock static methods.  For ordinary methods, expectations
+//! are set on the mock object.  But static methods don't have any mock object.
 //! Instead, you must create a `Context` object just to set their expectations.
 //!
+//! But be careful!  The expectations are global.  If you want to use a static
+//! method in multiple tests, you must provide some synchronization.  The
+//! easiest way is to create the `Context` with the method's
+//! `_context_exclusive` function, so it will also serve as a lock.  See the
+//! [`synchronization
+//! example`](https://github.com/asomers/mockall/blob/master/mockall/examples/synchronization.rs).
+//!
+//! While one thread holds an exclusive `Context`, another thread that tries to
+//! create one for the same method will block until the first is dropped.  That
+//! lets tests that mock the same static method run one at a time, even under
+//! the default parallel test runner.  A single thread may hold any number of exclusive `Context`s
+//! for the same method.  But beware: a test that holds exclusive `Context`s
+//! for several methods can deadlock with another test that creates them in a
+//! different order.
+//!
+//! Alternatively, with `#[automock(static_expectations = "thread_local")]`,
+//! the expectations of static methods, module functions, and foreign
+//! functions are stored per thread instead.  Each test thread sees only its
+//! own expectations, so tests never need to wait for each other.  But the
+//! code under test must then call the mocked method from the same thread that
+//! set its expectations.
+//!
+//! ```
+//! # use mockall::*;
+//! #[automock(static_expectations = "thread_local")]
+//! pub trait A {
+//!     fn foo() -> u32;
+//! }
+//!
+//! let ctx = MockA::foo_context();
+//! ctx.expect().returning(|| 99);
+//! assert_eq!(99, MockA::foo());
+//! std::thread::spawn(|| {
+//!     // This thread has its own, independent, expectations
+//!     let ctx = MockA::foo_context();
+//!     ctx.expect().returning(|| 42);
+//!     assert_eq!(42, MockA::foo());
+//! }).join().unwrap();
+//! ```
+//!
 //! ```
 //! # use mockall::*;
 //! #[automock]
@@ -1009,6 +1374,54 @@
 //! # fn main() {}
 //! ```
 //!
+//! A module whose source is in another file can be mocked with
+//! [`mock_module!`], because stable Rust doesn't allow attributes like
+//! `#[automock]` on out-of-line module declarations.
+//!
+//! ```ignore
+//! # use mockall::*;
+//! pub mod net;
+//! mock_module!(pub mod net;);
+//! ```
+//!
+//! Modules may contain other modules.  Those are mocked too, but keep their
+//! original names.  So `#[double]` can swap an entire tree of modules at once.
+//! Each module's `checkpoint` function also checkpoints its submodules.
+//!
+//! ```
+//! # use mockall::*;
+//! # use mockall_double::*;
+//! mod outer {
+//!     use mockall::automock;
+//!     #[automock]
+//!     pub mod api {
+//!         pub mod v1 {
+//!             pub fn get(id: u32) -> u32 {
+//!                 // ...
+//!                 # id
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! #[double]
+//! use outer::api;
+//!
+//! #[cfg(test)]
+//! mod t {
+//!     use super::*;
+//!
+//!     #[test]
+//!     fn test_get() {
+//!         let ctx = api::v1::get_context();
+//!         ctx.expect()
+//!             .returning(|id| id + 1);
+//!         assert_eq!(5, api::v1::get(4));
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+//!
 //! ### Foreign functions
 //!
 //! One reason to mock modules is when working with foreign functions.  Modules
@@ -1099,14 +1512,14 @@
 //!
 //! ## Crate features
 //!
-//! Mockall has a **nightly** feature.  Currently this feature has two
-//! effects:
+//! The **std_mocks** feature adds the [`std_mocks`](std_mocks/index.html)
+//! module, which contains ready-made mocks of commonly used standard library
+//! traits.  The **futures-io** feature adds mocks of `futures-io`'s
+//! `AsyncRead` and `AsyncWrite` traits to it, too.
 //!
-//! * The compiler will produce better error messages.
-//!
-//! * Expectations for methods whose return type implements `Default` needn't
-//!   have their return values explicitly set.  Instead, they will automatically
-//!   return the default value.
+//! Mockall also has a **nightly** feature.  With it, expectations for methods
+//! whose return type implements `Default` needn't have their return values
+//! explicitly set.  Instead, they will automatically return the default value.
 //!
 //! With **nightly** enabled, you can omit the return value like this:
 #![cfg_attr(feature = "nightly", doc = "```")]
@@ -1147,14 +1560,25 @@
 use downcast::*;
 use std::{
     any,
-    fmt::Debug,
+    cell::RefCell,
+    fmt::{self, Debug, Display},
+    future::Future,
     marker::PhantomData,
     ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo,
           RangeToInclusive},
+    panic::Location,
+    pin::Pin,
+    rc::Rc,
     sync::{
         Arc,
+        Condvar,
+        Mutex,
+        MutexGuard,
         atomic::{AtomicUsize, Ordering}
     },
+    task::{Context, Poll, Waker},
+    thread::{self, ThreadId},
+    time::Duration,
 };
 
 #[doc(hidden)]
@@ -1172,10 +1596,13 @@ pub use predicates::{
 #[doc(hidden)]
 pub use predicates_tree::CaseTreeExt;
 
-#[cfg(doc)]
+#[cfg(any(doc, feature = "std_mocks"))]
 extern crate self as mockall;
 #[cfg(doc)]
 pub mod examples;
+#[cfg(feature = "std_mocks")]
+#[cfg_attr(feature = "nightly", doc(cfg(feature = "std_mocks")))]
+pub mod std_mocks;
 
 /// Automatically generate mock types for structs and traits.
 ///
@@ -1289,17 +1716,17 @@ pub use mockall_derive::automock;
 /// can't be mocked.  The downsides of using this attribute are:
 ///
 /// * Mockall can't tell if a parameter isn't `'static`, so you must annotate
-/// such methods with the `#[mockall::concretize]` attribute.
+///   such methods with the `#[mockall::concretize]` attribute.
 /// * Generic methods will share expectations for all argument types.  That is,
 ///   you won't be able to do `my_mock.expect_foo::<i32>(...)`.
 /// * It can't be used on methods with a closure argument (though this may be
-/// fixable).
+///   fixable).
 /// * Concretized methods' expectations may only be matched with `.withf` or
-/// `.withf_st`, not `.with`.
+///   `.withf_st`, not `.with`.
 /// * It only works for parameters that can be turned into a trait object.
-/// (may be fixable).
+///   (may be fixable).
 /// * Mockall needs to know how to turn the function argument into a trait
-/// object.  Given a generic parameter `T`, currently supported patterns are:
+///   object.  Given a generic parameter `T`, currently supported patterns are:
 ///   - `T`
 ///   - `&T`
 ///   - `&mut T`
@@ -1426,6 +1853,34 @@ pub use mockall_derive::concretize;
 /// ```
 pub use mockall_derive::mock;
 
+/// Mock a module whose source is in another file.
+///
+/// `#[automock]` can't be used on an out-of-line module declaration like
+/// `mod net;` with a stable compiler.  Instead, repeat the declaration within
+/// `mock_module!`.  Mockall will find and parse the module's source the same
+/// way that rustc does, including obeying any `#[path]` attribute, and generate
+/// a `mock_net` module exactly as `#[automock]` would for an inline module.
+/// The module's own out-of-line submodules are found and mocked the same way.
+///
+/// ```ignore
+/// # use mockall::*;
+/// pub mod net;
+/// mock_module!(pub mod net;);
+///
+/// let ctx = mock_net::connect_context();
+/// ```
+///
+/// Compilers older than 1.88 can't tell Mockall which file invoked the macro.
+/// With those, the module must have a `#[path]` attribute, relative to the
+/// package's root directory.
+///
+/// ```ignore
+/// # use mockall::*;
+/// pub mod net;
+/// mock_module!(#[path = "src/net.rs"] pub mod net;);
+/// ```
+pub use mockall_derive::mock_module;
+
 #[doc(hidden)]
 pub trait AnyExpectations : Any + Send + Sync {}
 downcast!(dyn AnyExpectations);
@@ -1474,6 +1929,32 @@ pub struct DefaultReturner<O>(PhantomData<O>);
     }
 }
 
+// Wrapper type used by lenient mocks to create default return values.  Will
+// first try Default, otherwise will fall back to DefaultReturner, which can
+// handle generic types on nightly.
+#[doc(hidden)]
+pub struct DefaultMaker<O>(PhantomData<O>);
+impl<O> DefaultMaker<O> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        DefaultMaker(PhantomData)
+    }
+}
+#[doc(hidden)]
+pub trait ViaDefault<O> { fn make_default(&self) -> Result<O, &'static str>; }
+impl<O: Default> ViaDefault<O> for &DefaultMaker<O> {
+    fn make_default(&self) -> Result<O, &'static str> {
+        Ok(O::default())
+    }
+}
+#[doc(hidden)]
+pub trait ViaNoDefault<O> { fn make_default(&self) -> Result<O, &'static str>; }
+impl<O> ViaNoDefault<O> for DefaultMaker<O> {
+    fn make_default(&self) -> Result<O, &'static str> {
+        DefaultReturner::<O>::return_default()
+    }
+}
+
 // Wrapper type to allow for better expectation messages for any type.
 // Will first try Debug, otherwise will print '?'
 #[doc(hidden)]
@@ -1509,7 +1990,54 @@ impl<'a, T> ViaNothing for ArgPrinter<'a, T> {
     }
 }
 
-// Though it's not entirely correct, we treat usize::max_value() as
+/// A type-erased copy of one argument of a mock method's call.
+///
+/// Arguments that aren't `Clone + Send + 'static` can't be recorded; they are
+/// represented by an empty `ArgRecord`.
+#[derive(Default)]
+#[doc(hidden)]
+pub struct ArgRecord(Option<Box<dyn Fn() -> Box<dyn any::Any> + Send>>);
+
+impl ArgRecord {
+    fn new<O: Clone + Send + 'static>(o: O) -> Self {
+        ArgRecord(Some(Box::new(move || Box::new(o.clone()))))
+    }
+
+    /// Return a copy of the recorded argument, if it was recorded.
+    pub fn get<O: 'static>(&self) -> Option<O> {
+        self.0.as_ref()
+            .and_then(|f| f().downcast::<O>().ok())
+            .map(|o| *o)
+    }
+}
+
+// Wrapper type to allow for recording the arguments of any type.  Will first
+// try ToOwned, otherwise will record nothing.
+#[doc(hidden)]
+pub struct ArgRecorder<'a, T: ?Sized>(pub &'a T);
+
+#[doc(hidden)]
+pub trait ViaClone<T> where T: ToOwned + ?Sized {
+    fn record(&self) -> ArgRecord;
+}
+impl<'a, T> ViaClone<T> for &ArgRecorder<'a, T>
+    where T: ToOwned + ?Sized,
+          T::Owned: Clone + Send + 'static
+{
+    fn record(&self) -> ArgRecord {
+        ArgRecord::new(self.0.to_owned())
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaNoClone { fn record(&self) -> ArgRecord; }
+impl<'a, T: ?Sized> ViaNoClone for ArgRecorder<'a, T> {
+    fn record(&self) -> ArgRecord {
+        ArgRecord::default()
+    }
+}
+
+// Though it's not entirely correct, we treat usize::MAX as
 // approximately infinity.
 #[derive(Debug)]
 #[doc(hidden)]
@@ -1518,7 +2046,7 @@ pub struct TimesRange(Range<usize>);
 impl Default for TimesRange {
     fn default() -> TimesRange {
         // By default, allow any number of calls
-        TimesRange(0..usize::max_value())
+        TimesRange(0..usize::MAX)
     }
 }
 
@@ -1537,13 +2065,13 @@ impl From<Range<usize>> for TimesRange {
 
 impl From<RangeFrom<usize>> for TimesRange {
     fn from(r: RangeFrom<usize>) -> TimesRange {
-        TimesRange(r.start..usize::max_value())
+        TimesRange(r.start..usize::MAX)
     }
 }
 
 impl From<RangeFull> for TimesRange {
     fn from(_: RangeFull) -> TimesRange {
-        TimesRange(0..usize::max_value())
+        TimesRange(0..usize::MAX)
     }
 }
 
@@ -1602,7 +2130,7 @@ impl Times {
     }
 
     pub fn any(&mut self) {
-        self.range.0 = 0..usize::max_value();
+        self.range.0 = 0..usize::MAX;
     }
 
     /// Return how many times this expectation has been called
@@ -1678,47 +2206,433 @@ impl Key {
     }
 }
 
+/// A reentrant lock that serializes the use of a static method's `Context`
+/// objects.
+///
+/// Any number of `Context`s may exist on the thread that holds the lock.
+/// Other threads block until all of them have been dropped.
+#[doc(hidden)]
+pub struct ContextLock {
+    /// The owning thread, and how many `Context`s it holds
+    owner: Mutex<Option<(ThreadId, usize)>>,
+    released: Condvar
+}
+
+#[doc(hidden)]
+impl ContextLock {
+    pub const fn new() -> Self {
+        ContextLock {
+            owner: Mutex::new(None),
+            released: Condvar::new()
+        }
+    }
+
+    pub fn lock(&'static self) -> ContextLockGuard {
+        let me = thread::current().id();
+        let owner = self.owner.lock().unwrap();
+        let mut owner = self.released.wait_while(owner, |o| {
+            matches!(o, Some((tid, _)) if *tid != me)
+        }).unwrap();
+        match owner.as_mut() {
+            Some((_, count)) => *count += 1,
+            None => *owner = Some((me, 1))
+        }
+        ContextLockGuard(self)
+    }
+}
+
+impl Default for ContextLock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Holds a [`ContextLock`] until dropped.
+#[doc(hidden)]
+pub struct ContextLockGuard(&'static ContextLock);
+
+impl Drop for ContextLockGuard {
+    fn drop(&mut self) {
+        // Don't panic while unwinding from some other failure
+        let mut owner = match self.0.owner.lock() {
+            Ok(owner) => owner,
+            Err(e) => e.into_inner()
+        };
+        if let Some((_, count)) = owner.as_mut() {
+            *count -= 1;
+            if *count == 0 {
+                *owner = None;
+                drop(owner);
+                self.0.released.notify_all();
+            }
+        }
+    }
+}
+
 #[doc(hidden)]
+#[derive(Clone)]
 pub struct SeqHandle {
     inner: Arc<SeqInner>,
     seq: usize
 }
 
 impl SeqHandle {
-    /// Tell the Sequence that this expectation has been fully satisfied
+    /// Tell the Sequence whether this expectation may be skipped, because it
+    /// doesn't need to be called at all.
+    pub fn set_optional(&self, optional: bool) {
+        self.inner.set_satisfied(self.seq, optional);
+    }
+
+    /// Tell the Sequence that this expectation has reached its minimum call
+    /// count
     pub fn satisfy(&self) {
-        self.inner.satisfy(self.seq);
+        self.inner.set_satisfied(self.seq, true);
     }
 
     /// Verify that this handle was called in the correct order
     pub fn verify(&self, desc: &str) {
-        self.inner.verify(self.seq, desc);
+        self.inner.verify(self.seq, desc, None);
     }
 }
 
+#[derive(Default)]
+struct SeqState {
+    /// The latest step of the sequence to be called, if any
+    current: Option<usize>,
+    /// Has each step of the sequence reached its minimum call count?
+    satisfied: Vec<bool>,
+}
+
 #[derive(Default)]
 struct SeqInner {
-    satisfaction_level: AtomicUsize,
+    /// For a sequence created by `after`, a description of the ordering it
+    /// enforces
+    edge: Option<String>,
+    state: Mutex<SeqState>,
 }
 
 impl SeqInner {
-    /// Record the call identified by `seq` as fully satisfied.
-    fn satisfy(&self, seq: usize) {
-        let old_sl = self.satisfaction_level.fetch_add(1, Ordering::Relaxed);
-        assert_eq!(old_sl, seq, "Method sequence violation.  Was an already-satisfied method called another time?");
+    /// Add a new step to the end of the sequence, returning its index
+    fn push(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.satisfied.push(false);
+        state.satisfied.len() - 1
+    }
+
+    /// Record whether the step identified by `seq` has reached its minimum
+    /// call count.
+    fn set_satisfied(&self, seq: usize, satisfied: bool) {
+        self.state.lock().unwrap().satisfied[seq] = satisfied;
     }
 
-    /// Verify that the call identified by `seq` was called in the correct order
-    fn verify(&self, seq: usize, desc: &str) {
-        assert_eq!(seq, self.satisfaction_level.load(Ordering::Relaxed),
-            "{desc}: Method sequence violation")
+    /// Verify that the call identified by `seq` was called in the correct
+    /// order.  That is, no later step has been called yet, and every earlier
+    /// step since the latest one called has been satisfied.
+    ///
+    /// `location`, if known, is where the expectation was created.
+    fn verify(&self, seq: usize, desc: &str,
+              location: Option<&'static Location<'static>>)
+    {
+        let ok = {
+            let mut state = self.state.lock().unwrap();
+            let start = state.current.unwrap_or(0);
+            let ok = seq >= start &&
+                state.satisfied[start..seq].iter().all(|s| *s);
+            if ok {
+                state.current = Some(seq);
+            }
+            ok
+        };
+        if ok {
+            return;
+        }
+        let at = location.map(|l| format!(" (expectation set at {l})"))
+            .unwrap_or_default();
+        let message = if let Some(edge) = &self.edge {
+            format!("{desc}: Method sequence violation: {edge}{at}")
+        } else {
+            format!("{desc}: Method sequence violation{at}")
+        };
+        fail(FailureKind::SequenceViolation, message);
+    }
+}
+
+#[derive(Default)]
+struct SeqHandlesInner {
+    handles: Vec<SeqHandle>,
+    /// Has the expectation reached its minimum call count?
+    satisfied: bool,
+    /// Tasks awaiting a `Completion` for the expectation
+    wakers: Vec<Waker>,
+}
+
+/// Every sequence that a single expectation belongs to.
+#[doc(hidden)]
+#[derive(Clone, Default)]
+pub struct SeqHandles(Arc<SeqHandlesShared>);
+
+#[derive(Default)]
+struct SeqHandlesShared {
+    inner: Mutex<SeqHandlesInner>,
+    /// Notified whenever the expectation becomes satisfied
+    satisfied: Condvar,
+}
+
+impl SeqHandles {
+    /// Require the expectation to be called only after the one identified by
+    /// `pred`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pred`:   Handle to the expectation that must be called first
+    /// * `desc`:   Description of this expectation
+    pub fn after(&self, pred: &ExpectationHandle, desc: &str) {
+        let mut seq = Sequence {
+            inner: Arc::new(SeqInner {
+                edge: Some(format!("{} must be called before {}",
+                                   pred.desc, desc)),
+                state: Mutex::default()
+            })
+        };
+        pred.handles.push(&mut seq);
+        self.push(&mut seq);
+    }
+
+    /// Create a handle that other expectations can be ordered after.
+    ///
+    /// # Arguments
+    ///
+    /// * `desc`:   Description of this expectation
+    pub fn handle(&self, desc: String) -> ExpectationHandle {
+        ExpectationHandle {
+            handles: self.clone(),
+            desc
+        }
+    }
+
+    /// Add the expectation to the end of `seq`.
+    pub fn push(&self, seq: &mut Sequence) {
+        let handle = seq.next_handle();
+        let mut inner = self.0.inner.lock().unwrap();
+        handle.set_optional(inner.satisfied);
+        inner.handles.push(handle);
+    }
+
+    /// Tell every sequence whether it may skip the expectation, because it
+    /// has already reached its minimum call count.
+    pub fn set_optional(&self, optional: bool) {
+        let mut inner = self.0.inner.lock().unwrap();
+        inner.satisfied = optional;
+        for handle in inner.handles.iter() {
+            handle.set_optional(optional);
+        }
+        if optional {
+            self.notify(inner);
+        }
+    }
+
+    /// Tell every sequence, and anybody waiting, that the expectation has
+    /// reached its minimum call count
+    pub fn satisfy(&self) {
+        let mut inner = self.0.inner.lock().unwrap();
+        inner.satisfied = true;
+        for handle in inner.handles.iter() {
+            handle.satisfy();
+        }
+        self.notify(inner);
+    }
+
+    /// Create a future that completes once the expectation is satisfied.
+    pub fn completion(&self) -> Completion {
+        Completion(self.clone())
+    }
+
+    /// Wake every thread and task waiting for the expectation to be satisfied.
+    fn notify(&self, mut inner: MutexGuard<'_, SeqHandlesInner>) {
+        let wakers = std::mem::take(&mut inner.wakers);
+        drop(inner);
+        self.0.satisfied.notify_all();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Verify that the expectation was called in the correct order, in every
+    /// sequence.
+    ///
+    /// # Arguments
+    ///
+    /// * `desc`:       Description of the call
+    /// * `location`:   Where the expectation was created, if known
+    pub fn verify(&self, desc: &str,
+                  location: Option<&'static Location<'static>>)
+    {
+        // Don't panic while holding the lock
+        let handles = self.0.inner.lock().unwrap().handles.clone();
+        for handle in handles.iter() {
+            handle.inner.verify(handle.seq, desc, location);
+        }
+    }
+}
+
+/// Identifies an expectation, so that others can be required to happen after
+/// it.
+///
+/// Create one with an expectation's `handle` method, and use it with another
+/// expectation's `after` method.  Unlike a [`Sequence`], this can express
+/// partial orderings.
+///
+/// # Examples
+/// ```
+/// # use mockall::*;
+/// #[automock]
+/// trait Foo {
+///     fn a(&self);
+///     fn b(&self);
+///     fn c(&self);
+/// }
+/// let mut mock = MockFoo::new();
+/// let a = mock.expect_a()
+///     .times(1)
+///     .returning(|| ())
+///     .handle();
+/// let b = mock.expect_b()
+///     .times(1)
+///     .returning(|| ())
+///     .handle();
+/// mock.expect_c()
+///     .times(1)
+///     .returning(|| ())
+///     .after(&a)
+///     .after(&b);
+///
+/// // a and b may be called in either order, but both before c
+/// mock.b();
+/// mock.a();
+/// mock.c();
+/// ```
+#[derive(Clone)]
+pub struct ExpectationHandle {
+    handles: SeqHandles,
+    desc: String
+}
+
+impl ExpectationHandle {
+    /// Create a future that completes once the expectation has been called at
+    /// least as many times as it requires.
+    ///
+    /// See [`Completion`].
+    pub fn completion(&self) -> Completion {
+        self.handles.completion()
+    }
+
+    /// Block until the expectation has been called at least as many times as
+    /// it requires, or until `timeout` elapses.
+    ///
+    /// This is useful when the code under test calls the mock from another
+    /// thread.
+    ///
+    /// An expectation whose call count was never set requires no calls at
+    /// all, so `wait` returns immediately for it.  Set a count, like
+    /// `.times(1..)`, to wait for at least one call.
+    ///
+    /// # Panics
+    ///
+    /// If the expectation still isn't satisfied after `timeout`, reports a
+    /// [`FailureKind::TooFewCalls`] failure.  Without a [`FailureHandler`],
+    /// that panics.
+    ///
+    /// # Examples
+    /// ```
+    /// # use mockall::*;
+    /// # use std::{sync::Arc, thread, time::Duration};
+    /// #[automock]
+    /// trait Foo {
+    ///     fn foo(&self, x: u32);
+    /// }
+    /// let mut mock = MockFoo::new();
+    /// let handle = mock.expect_foo()
+    ///     .times(2)
+    ///     .return_const(())
+    ///     .handle();
+    /// let mock = Arc::new(mock);
+    /// let mock2 = mock.clone();
+    /// thread::spawn(move || {
+    ///     mock2.foo(1);
+    ///     mock2.foo(2);
+    /// });
+    /// handle.wait(Duration::from_secs(60));
+    /// ```
+    pub fn wait(&self, timeout: Duration) {
+        let shared = &self.handles.0;
+        let inner = shared.inner.lock().unwrap();
+        let (inner, result) = shared.satisfied
+            .wait_timeout_while(inner, timeout, |inner| !inner.satisfied)
+            .unwrap();
+        // Don't panic while holding the lock
+        drop(inner);
+        if result.timed_out() {
+            fail(FailureKind::TooFewCalls,
+                format!("{}: not satisfied after waiting {:?}", self.desc,
+                        timeout));
+        }
+    }
+}
+
+/// A future that completes once an expectation has been called at least as
+/// many times as it requires.
+///
+/// This is the asynchronous counterpart to [`ExpectationHandle::wait`], for
+/// tests whose mocks are called from other tasks.  It works with any async
+/// runtime.  Create one with an expectation's `completion` method.
+///
+/// Like `wait`, it completes immediately for an expectation whose call count
+/// was never set.
+///
+/// # Examples
+/// ```
+/// # use mockall::*;
+/// # use futures::executor::block_on;
+/// #[automock]
+/// trait Foo {
+///     fn foo(&self, x: u32);
+/// }
+/// let mut mock = MockFoo::new();
+/// let done = mock.expect_foo()
+///     .times(3)
+///     .return_const(())
+///     .completion();
+/// # let mock = std::sync::Arc::new(mock);
+/// # let mock2 = mock.clone();
+/// # std::thread::spawn(move || for i in 0..3 { mock2.foo(i) });
+/// // ... spawn some tasks that call mock.foo ...
+/// block_on(done);
+/// ```
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Completion(SeqHandles);
+
+impl Future for Completion {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut inner = self.0.0.inner.lock().unwrap();
+        if inner.satisfied {
+            Poll::Ready(())
+        } else {
+            if !inner.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                inner.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        }
     }
 }
 
 /// Used to enforce that mock calls must happen in the sequence specified.
 ///
-/// Each expectation must expect to be called a fixed number of times.  Once
-/// satisfied, the next expectation in the sequence will expect to be called.
+/// Once an expectation has been called its minimum number of times, the next
+/// expectation in the sequence may be called.  An expectation that accepts a
+/// range of call counts may keep being called until a later one is.  And an
+/// expectation that needn't be called at all may be skipped.
 ///
 /// # Examples
 /// ```
@@ -1747,25 +2661,34 @@ impl SeqInner {
 /// mock1.bar();
 /// ```
 ///
-/// It is an error to add an expectation to a `Sequence` if its call count is
-/// unspecified.
-/// ```should_panic(expected = "with an exact call count")
+/// Expectations with a range of call counts can be used to allow any number
+/// of some calls before another.
+/// ```
 /// # use mockall::*;
 /// #[automock]
-/// trait Foo {
-///     fn foo(&self);
+/// trait Conn {
+///     fn heartbeat(&self);
+///     fn close(&self);
 /// }
 /// let mut seq = Sequence::new();
 ///
-/// let mut mock = MockFoo::new();
-/// mock.expect_foo()
+/// let mut mock = MockConn::new();
+/// mock.expect_heartbeat()
+///     .times(..)
 ///     .returning(|| ())
-///     .in_sequence(&mut seq);  // panics!
+///     .in_sequence(&mut seq);
+/// mock.expect_close()
+///     .times(1)
+///     .returning(|| ())
+///     .in_sequence(&mut seq);
+///
+/// mock.heartbeat();
+/// mock.heartbeat();
+/// mock.close();
 /// ```
 #[derive(Default)]
 pub struct Sequence {
     inner: Arc<SeqInner>,
-    next_seq: usize,
 }
 
 impl Sequence {
@@ -1778,8 +2701,233 @@ impl Sequence {
     /// can call it.
     #[doc(hidden)]
     pub fn next_handle(&mut self) -> SeqHandle {
-        let handle = SeqHandle{inner: self.inner.clone(), seq: self.next_seq};
-        self.next_seq += 1;
-        handle
+        let seq = self.inner.push();
+        SeqHandle{inner: self.inner.clone(), seq}
+    }
+}
+
+/// A predicate that matches any argument, and records a copy of each one it
+/// sees.
+///
+/// This is useful when an argument can't be predicted up front, like a
+/// generated ID, but the test still needs to inspect it afterwards.  Clones of
+/// a `Captor` share the same storage, so pass a clone to
+/// `with` and keep the original.
+///
+/// A `Captor` records every argument that it's evaluated against.  That
+/// includes calls rejected by the expectation's other predicates, and calls
+/// that were matched to an earlier expectation first.
+///
+/// # Examples
+/// ```
+/// # use mockall::*;
+/// #[automock]
+/// trait Foo {
+///     fn foo(&self, id: u64, name: &str);
+/// }
+///
+/// let id = Captor::new();
+/// let name = Captor::new();
+/// let mut mock = MockFoo::new();
+/// mock.expect_foo()
+///     .with(id.clone(), name.clone())
+///     .return_const(());
+///
+/// mock.foo(1234, "alice");
+/// mock.foo(5678, "bob");
+/// assert_eq!(id.values(), vec![1234, 5678]);
+/// assert_eq!(name.last().as_deref(), Some("bob"));
+/// ```
+pub struct Captor<T>(Arc<Mutex<Vec<T>>>);
+
+impl<T> Captor<T> {
+    /// Create a new `Captor` that hasn't captured anything yet.
+    pub fn new() -> Self {
+        Captor(Arc::new(Mutex::new(Vec::new())))
+    }
+
+    /// Return the most recently captured argument, if any.
+    pub fn last(&self) -> Option<T>
+        where T: Clone
+    {
+        self.0.lock().unwrap().last().cloned()
+    }
+
+    /// Return every captured argument, in order.
+    pub fn values(&self) -> Vec<T>
+        where T: Clone
+    {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl<T> Clone for Captor<T> {
+    fn clone(&self) -> Self {
+        Captor(self.0.clone())
+    }
+}
+
+impl<T> Default for Captor<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::fmt::Display for Captor<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<captor>")
+    }
+}
+
+impl<T> predicates::reflection::PredicateReflection for Captor<T> {}
+
+impl<T, P> Predicate<P> for Captor<T>
+    where P: ToOwned<Owned = T> + ?Sized
+{
+    fn eval(&self, variable: &P) -> bool {
+        self.0.lock().unwrap().push(variable.to_owned());
+        true
+    }
+
+    // Unlike eval, don't capture anything.  This is only used to explain
+    // failures.
+    fn find_case<'a>(&'a self, expected: bool, _variable: &P)
+        -> Option<predicates::reflection::Case<'a>>
+    {
+        if expected {
+            Some(predicates::reflection::Case::new(Some(self), true))
+        } else {
+            None
+        }
+    }
+}
+
+/// The kind of a [`Failure`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum FailureKind {
+    /// A method was called, but none of its expectations matched.
+    UnexpectedCall,
+    /// An expectation was called fewer times than required.
+    TooFewCalls,
+    /// An expectation was called more times than allowed.
+    TooManyCalls,
+    /// An expectation was called out of order.
+    SequenceViolation,
+    /// An expectation was called, but had no return value to give.
+    MissingReturnValue,
+}
+
+/// A mock failure, as reported to a [`FailureHandler`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Failure {
+    /// What went wrong
+    pub kind: FailureKind,
+    /// The message that Mockall would otherwise panic with
+    pub message: String,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
     }
 }
+
+/// Receives mock failures instead of letting Mockall panic.
+///
+/// Install one for the current thread with [`set_failure_handler`].  Any
+/// `Fn(&Failure)` closure is a `FailureHandler`.
+///
+/// After the handler returns, the mock carries on as well as it can.  For
+/// example, a call beyond an expectation's maximum count will still return
+/// that expectation's value.  But some failures, like an unexpected call or a
+/// missing return value, leave the mock with nothing to return.  For those,
+/// Mockall panics anyway after the handler returns.
+pub trait FailureHandler {
+    /// Handle a single failure.
+    fn handle(&self, failure: &Failure);
+}
+
+impl<F: Fn(&Failure)> FailureHandler for F {
+    fn handle(&self, failure: &Failure) {
+        self(failure)
+    }
+}
+
+thread_local! {
+    static FAILURE_HANDLER: RefCell<Option<Rc<dyn FailureHandler>>> =
+        const { RefCell::new(None) };
+}
+
+/// Restores the previous [`FailureHandler`] when dropped.
+///
+/// Returned by [`set_failure_handler`].
+#[must_use = "the failure handler is uninstalled when this guard is dropped"]
+pub struct FailureHandlerGuard {
+    prev: Option<Rc<dyn FailureHandler>>,
+}
+
+impl Drop for FailureHandlerGuard {
+    fn drop(&mut self) {
+        let prev = self.prev.take();
+        FAILURE_HANDLER.with(|h| *h.borrow_mut() = prev);
+    }
+}
+
+/// Send mock failures on the current thread to `handler` instead of
+/// panicking, until the returned guard is dropped.
+///
+/// Failures are reported on the thread where they are detected.  That's the
+/// thread that called the mock, or for unsatisfied call counts, the thread
+/// that dropped or checkpointed it.
+///
+/// # Examples
+/// ```
+/// # use mockall::*;
+/// # use std::{cell::RefCell, rc::Rc};
+/// #[automock]
+/// trait Foo {
+///     fn foo(&self);
+/// }
+///
+/// let failures = Rc::new(RefCell::new(Vec::new()));
+/// let failures2 = failures.clone();
+/// let _guard = set_failure_handler(move |f: &Failure| {
+///     failures2.borrow_mut().push(f.kind);
+/// });
+/// let mut mock = MockFoo::new();
+/// mock.expect_foo()
+///     .times(1)
+///     .return_const(());
+/// drop(mock);
+/// assert_eq!(*failures.borrow(), vec![FailureKind::TooFewCalls]);
+/// ```
+pub fn set_failure_handler<H>(handler: H) -> FailureHandlerGuard
+    where H: FailureHandler + 'static
+{
+    let handler: Rc<dyn FailureHandler> = Rc::new(handler);
+    let prev = FAILURE_HANDLER.with(|h| h.borrow_mut().replace(handler));
+    FailureHandlerGuard{prev}
+}
+
+/// Report a failure to the current thread's [`FailureHandler`], or panic if
+/// there isn't one.
+#[doc(hidden)]
+#[track_caller]
+pub fn fail(kind: FailureKind, message: String) {
+    let handler = FAILURE_HANDLER.with(|h| h.borrow().clone());
+    if let Some(handler) = handler {
+        handler.handle(&Failure{kind, message});
+    } else {
+        panic!("{message}");
+    }
+}
+
+/// Report a failure that the mock can't recover from, then panic.
+#[doc(hidden)]
+#[track_caller]
+pub fn fail_fatal(kind: FailureKind, message: String) -> ! {
+    fail(kind, message.clone());
+    panic!("{message}");
+}
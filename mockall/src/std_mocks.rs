@@ -0,0 +1,373 @@
+// vim: tw=80
+//! Ready-made mocks of commonly used standard library traits
+//!
+//! These save each crate from declaring its own mocks of `std` traits with
+//! [`mock!`](crate::mock).  They're only available with the **std_mocks**
+//! feature.
+//!
+//! A few trait methods return references that borrow from `self`, which Mockall
+//! can't return from within an `io::Result` or `Option`.  Those mocks' methods
+//! return `'static` references instead, so their expectations must return
+//! `'static` data, like a byte string literal.
+//!
+//! # Examples
+//!
+//! ```
+//! # use mockall::std_mocks::MockRead;
+//! # use std::io::Read;
+//! let mut mock = MockRead::new();
+//! mock.expect_read()
+//!     .returning(|buf| {
+//!         buf[..5].copy_from_slice(b"hello");
+//!         Ok(5)
+//!     });
+//! let mut buf = [0u8; 16];
+//! assert_eq!(5, mock.read(&mut buf).unwrap());
+//! assert_eq!(b"hello", &buf[..5]);
+//! ```
+//!
+//! # Scripted I/O
+//!
+//! Testing how code copes with short reads and transient errors is tedious
+//! with raw expectations.  Instead, [`MockRead::scripted`] creates a mock that
+//! replays a script of [`ReadStep`]s, and [`MockWrite::scripted`] creates one
+//! that follows a script of [`WriteStep`]s and records everything written to a
+//! [`WriteLog`].
+//!
+//! ```
+//! # use mockall::std_mocks::*;
+//! # use std::io::{self, Read, Write};
+//! let mut reader = MockRead::scripted([
+//!     ReadStep::Data(b"hello ".to_vec()),
+//!     ReadStep::Error(io::ErrorKind::Interrupted),
+//!     ReadStep::Data(b"world".to_vec()),
+//! ]);
+//! let mut s = String::new();
+//! reader.read_to_string(&mut s).unwrap();
+//! assert_eq!(s, "hello world");
+//!
+//! let (mut writer, log) = MockWrite::scripted([WriteStep::Accept(3)]);
+//! writer.write_all(b"hello").unwrap();
+//! assert_eq!(log.contents(), b"hello");
+//! ```
+//!
+//! With the **futures-io** feature,
+//! [`MockAsyncRead`](struct.MockAsyncRead.html) and
+//! [`MockAsyncWrite`](struct.MockAsyncWrite.html) mock the `futures-io` crate's
+//! traits, and have the same scripted constructors.
+use std::{
+    collections::VecDeque,
+    error,
+    fmt,
+    hash,
+    io,
+    str,
+    sync::{Arc, Mutex, PoisonError},
+};
+#[cfg(feature = "futures-io")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::mock;
+
+/// One step of a [`MockRead::scripted`] script
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReadStep {
+    /// Return this data.  If the caller's buffer is too small, the remainder
+    /// will be returned by the following reads.
+    Data(Vec<u8>),
+    /// Fail with an error of this kind, like `WouldBlock` or `Interrupted`.
+    Error(io::ErrorKind),
+    /// Return end-of-file once, even if more steps follow.
+    Eof,
+}
+
+/// One step of a [`MockWrite::scripted`] script
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum WriteStep {
+    /// Accept at most this many bytes.
+    Accept(usize),
+    /// Fail with an error of this kind, like `WouldBlock` or `Interrupted`.
+    Error(io::ErrorKind),
+}
+
+/// Everything written to a scripted [`MockWrite`]
+///
+/// Clones share the same contents, so the test can keep one while the mock
+/// is moved into the code under test.
+#[derive(Clone, Debug, Default)]
+pub struct WriteLog(Arc<Mutex<Vec<u8>>>);
+
+impl WriteLog {
+    /// Return a copy of all bytes written so far.
+    pub fn contents(&self) -> Vec<u8> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+/// The state of a scripted reader
+#[derive(Debug)]
+struct ReadScript(VecDeque<ReadStep>);
+
+impl ReadScript {
+    fn new<I: IntoIterator<Item = ReadStep>>(script: I) -> Self {
+        ReadScript(script.into_iter().collect())
+    }
+
+    /// Perform the next step of the script.  After the last one, always return
+    /// EOF.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.pop_front() {
+            None | Some(ReadStep::Eof) => Ok(0),
+            Some(ReadStep::Error(kind)) => Err(kind.into()),
+            Some(ReadStep::Data(mut data)) => {
+                let n = data.len().min(buf.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                if n < data.len() {
+                    data.drain(..n);
+                    self.0.push_front(ReadStep::Data(data));
+                }
+                Ok(n)
+            }
+        }
+    }
+}
+
+/// The state of a scripted writer
+#[derive(Debug)]
+struct WriteScript(VecDeque<WriteStep>, WriteLog);
+
+impl WriteScript {
+    fn new<I: IntoIterator<Item = WriteStep>>(script: I) -> Self {
+        WriteScript(script.into_iter().collect(), WriteLog::default())
+    }
+
+    /// Perform the next step of the script.  After the last one, always accept
+    /// the entire buffer.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = match self.0.pop_front() {
+            None => buf.len(),
+            Some(WriteStep::Accept(n)) => n.min(buf.len()),
+            Some(WriteStep::Error(kind)) => return Err(kind.into())
+        };
+        (self.1).0.lock().unwrap_or_else(PoisonError::into_inner)
+            .extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Convert a synchronous I/O result into an asynchronous one.
+///
+/// `WouldBlock` becomes `Pending`, after waking the task so it will try again.
+#[cfg(feature = "futures-io")]
+fn poll_result<T>(cx: &mut Context<'_>, r: io::Result<T>)
+    -> Poll<io::Result<T>>
+{
+    match r {
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        },
+        r => Poll::Ready(r)
+    }
+}
+
+mock! {
+    /// Mock of [`std::io::Read`]
+    #[derive(Debug)]
+    pub Read {}
+    impl io::Read for Read {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+    }
+}
+
+mock! {
+    /// Mock of [`std::io::Write`]
+    #[derive(Debug)]
+    pub Write {}
+    impl io::Write for Write {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize>;
+        fn flush(&mut self) -> io::Result<()>;
+    }
+}
+
+impl MockRead {
+    /// Create a mock that replays `script`, then returns EOF.
+    pub fn scripted<I: IntoIterator<Item = ReadStep>>(script: I) -> Self {
+        let mut script = ReadScript::new(script);
+        let mut mock = Self::new();
+        mock.expect_read()
+            .returning(move |buf| script.read(buf));
+        mock
+    }
+}
+
+impl MockWrite {
+    /// Create a mock that accepts all writes and records them.
+    pub fn recording() -> (Self, WriteLog) {
+        Self::scripted([])
+    }
+
+    /// Create a mock that follows `script`, then accepts all writes.
+    ///
+    /// Every accepted byte is recorded in the returned [`WriteLog`].  `flush`
+    /// always succeeds.
+    pub fn scripted<I>(script: I) -> (Self, WriteLog)
+        where I: IntoIterator<Item = WriteStep>
+    {
+        let mut script = WriteScript::new(script);
+        let log = script.1.clone();
+        let mut mock = Self::new();
+        mock.expect_write()
+            .returning(move |buf| script.write(buf));
+        mock.expect_flush()
+            .returning(|| Ok(()));
+        (mock, log)
+    }
+}
+
+#[cfg(feature = "futures-io")]
+mock! {
+    /// Mock of [`futures_io::AsyncRead`]
+    #[derive(Debug)]
+    pub AsyncRead {}
+    impl futures_io::AsyncRead for AsyncRead {
+        fn poll_read<'a>(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'a>,
+            buf: &mut [u8]
+        ) -> Poll<io::Result<usize>>;
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl MockAsyncRead {
+    /// Create a mock that replays `script`, then returns EOF.
+    ///
+    /// A `WouldBlock` step returns `Poll::Pending`.
+    pub fn scripted<I: IntoIterator<Item = ReadStep>>(script: I) -> Self {
+        let mut script = ReadScript::new(script);
+        let mut mock = Self::new();
+        mock.expect_poll_read()
+            .returning(move |cx, buf| poll_result(cx, script.read(buf)));
+        mock
+    }
+}
+
+#[cfg(feature = "futures-io")]
+mock! {
+    /// Mock of [`futures_io::AsyncWrite`]
+    #[derive(Debug)]
+    pub AsyncWrite {}
+    impl futures_io::AsyncWrite for AsyncWrite {
+        fn poll_write<'a>(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'a>,
+            buf: &[u8]
+        ) -> Poll<io::Result<usize>>;
+        fn poll_flush<'a>(self: Pin<&mut Self>, cx: &mut Context<'a>)
+            -> Poll<io::Result<()>>;
+        fn poll_close<'a>(self: Pin<&mut Self>, cx: &mut Context<'a>)
+            -> Poll<io::Result<()>>;
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl MockAsyncWrite {
+    /// Create a mock that accepts all writes and records them.
+    pub fn recording() -> (Self, WriteLog) {
+        Self::scripted([])
+    }
+
+    /// Create a mock that follows `script`, then accepts all writes.
+    ///
+    /// Every accepted byte is recorded in the returned [`WriteLog`].  A
+    /// `WouldBlock` step returns `Poll::Pending`.  `poll_flush` and
+    /// `poll_close` always succeed.
+    pub fn scripted<I>(script: I) -> (Self, WriteLog)
+        where I: IntoIterator<Item = WriteStep>
+    {
+        let mut script = WriteScript::new(script);
+        let log = script.1.clone();
+        let mut mock = Self::new();
+        mock.expect_poll_write()
+            .returning(move |cx, buf| poll_result(cx, script.write(buf)));
+        mock.expect_poll_flush()
+            .returning(|_| Poll::Ready(Ok(())));
+        mock.expect_poll_close()
+            .returning(|_| Poll::Ready(Ok(())));
+        (mock, log)
+    }
+}
+
+mock! {
+    /// Mock of [`std::io::BufRead`]
+    ///
+    /// `fill_buf` returns a `'static` slice.
+    #[derive(Debug)]
+    pub BufRead {}
+    impl io::Read for BufRead {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+    }
+    impl io::BufRead for BufRead {
+        fn fill_buf(&mut self) -> io::Result<&'static [u8]>;
+        fn consume(&mut self, amt: usize);
+    }
+}
+
+mock! {
+    /// Mock of [`std::iter::Iterator`]
+    #[derive(Debug)]
+    pub Iterator<T: 'static> {}
+    impl<T: 'static> Iterator for Iterator<T> {
+        type Item = T;
+        fn next(&mut self) -> Option<T>;
+    }
+}
+
+mock! {
+    /// Mock of [`std::hash::Hasher`]
+    #[derive(Debug)]
+    pub Hasher {}
+    impl hash::Hasher for Hasher {
+        fn finish(&self) -> u64;
+        fn write(&mut self, bytes: &[u8]);
+    }
+}
+
+mock! {
+    /// Mock of [`std::fmt::Display`]
+    #[derive(Debug)]
+    pub Display {}
+    impl fmt::Display for Display {
+        fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result;
+    }
+}
+
+mock! {
+    /// Mock of [`std::str::FromStr`]
+    ///
+    /// `E` is the trait's associated `Err` type.
+    #[derive(Debug)]
+    pub FromStr<E: 'static> {}
+    impl<E: 'static> str::FromStr for FromStr<E> {
+        type Err = E;
+        fn from_str(s: &str) -> Result<Self, E>;
+    }
+}
+
+mock! {
+    /// Mock of [`std::error::Error`]
+    ///
+    /// `source` returns a `'static` reference.
+    #[derive(Debug)]
+    pub Error {}
+    impl fmt::Display for Error {
+        fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result;
+    }
+    impl error::Error for Error {
+        fn source(&self) -> Option<&'static (dyn error::Error + 'static)>;
+    }
+}
@@ -0,0 +1,193 @@
+// vim: tw=80
+//! Expectations may belong to several sequences, and may be ordered after
+//! other expectations
+#![deny(warnings)]
+
+use mockall::*;
+
+#[automock]
+trait Foo {
+    fn a(&self);
+    fn b(&self);
+    fn c(&self, x: u32);
+    fn d(&self);
+}
+
+/// Build a mock where a and b must both precede c, in either order
+fn diamond() -> MockFoo {
+    let mut mock = MockFoo::new();
+    let a = mock.expect_a()
+        .times(1)
+        .return_const(())
+        .handle();
+    let b = mock.expect_b()
+        .times(1)
+        .return_const(())
+        .handle();
+    mock.expect_c()
+        .times(1)
+        .return_const(())
+        .after(&a)
+        .after(&b);
+    mock
+}
+
+#[test]
+fn after_ok() {
+    let mock = diamond();
+    mock.a();
+    mock.b();
+    mock.c(0);
+}
+
+#[test]
+fn after_either_order() {
+    let mock = diamond();
+    mock.b();
+    mock.a();
+    mock.c(0);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::c(0): Method sequence violation: MockFoo::b: Expectation(<anything>) must be called before MockFoo::c: Expectation(<anything>)")]
+fn after_too_early() {
+    let mock = diamond();
+    mock.a();
+    mock.c(0);
+}
+
+/// The predecessor may not be called once its successor has been
+#[test]
+#[should_panic(expected = "MockFoo::a(): Method sequence violation: MockFoo::a: Expectation(<anything>) must be called before MockFoo::c: Expectation(<anything>)")]
+fn after_predecessor_late() {
+    let mut mock = MockFoo::new();
+    let a = mock.expect_a()
+        .times(0..)
+        .return_const(())
+        .handle();
+    mock.expect_c()
+        .times(1)
+        .return_const(())
+        .after(&a);
+    mock.c(0);
+    mock.a();
+}
+
+/// A predecessor that need not be called at all may be skipped
+#[test]
+fn after_optional() {
+    let mut mock = MockFoo::new();
+    let a = mock.expect_a()
+        .times(0..)
+        .return_const(())
+        .handle();
+    mock.expect_c()
+        .times(1)
+        .return_const(())
+        .after(&a);
+    mock.c(0);
+}
+
+/// A handle's predecessor is satisfied by being called enough times
+#[test]
+#[should_panic(expected = "MockFoo::c(0): Method sequence violation")]
+fn after_range_minimum() {
+    let mut mock = MockFoo::new();
+    let a = mock.expect_a()
+        .times(2..)
+        .return_const(())
+        .handle();
+    mock.expect_c()
+        .times(1)
+        .return_const(())
+        .after(&a);
+    mock.a();
+    mock.c(0);
+}
+
+/// Successors of the same predecessor are not ordered with respect to each
+/// other
+#[test]
+fn after_fanout() {
+    let mut mock = MockFoo::new();
+    let a = mock.expect_a()
+        .times(1)
+        .return_const(())
+        .handle();
+    mock.expect_c()
+        .times(1)
+        .return_const(())
+        .after(&a);
+    mock.expect_d()
+        .times(1)
+        .return_const(())
+        .after(&a);
+    mock.a();
+    mock.d();
+    mock.c(0);
+}
+
+/// The violation message identifies the predecessor by its matcher
+#[test]
+#[should_panic(expected = "MockFoo::c(1): Method sequence violation: MockFoo::c: Expectation(var == 0) must be called before MockFoo::c: Expectation(var == 1)")]
+fn after_same_method() {
+    let mut mock = MockFoo::new();
+    let c0 = mock.expect_c()
+        .with(predicate::eq(0))
+        .times(1)
+        .return_const(())
+        .handle();
+    mock.expect_c()
+        .with(predicate::eq(1))
+        .times(1)
+        .return_const(())
+        .after(&c0);
+    mock.c(1);
+}
+
+#[test]
+fn multiple_sequences_ok() {
+    let mut seq1 = Sequence::new();
+    let mut seq2 = Sequence::new();
+    let mut mock = MockFoo::new();
+    mock.expect_a()
+        .times(1)
+        .return_const(())
+        .in_sequence(&mut seq1);
+    mock.expect_b()
+        .times(1)
+        .return_const(())
+        .in_sequence(&mut seq2);
+    mock.expect_c()
+        .times(1)
+        .return_const(())
+        .in_sequence(&mut seq1)
+        .in_sequence(&mut seq2);
+    mock.b();
+    mock.a();
+    mock.c(0);
+}
+
+/// Joining a second sequence doesn't remove an expectation from the first
+#[test]
+#[should_panic(expected = "MockFoo::c(0): Method sequence violation")]
+fn multiple_sequences_violation() {
+    let mut seq1 = Sequence::new();
+    let mut seq2 = Sequence::new();
+    let mut mock = MockFoo::new();
+    mock.expect_a()
+        .times(1)
+        .return_const(())
+        .in_sequence(&mut seq1);
+    mock.expect_b()
+        .times(1)
+        .return_const(())
+        .in_sequence(&mut seq2);
+    mock.expect_c()
+        .times(1)
+        .return_const(())
+        .in_sequence(&mut seq1)
+        .in_sequence(&mut seq2);
+    mock.b();
+    mock.c(0);
+}
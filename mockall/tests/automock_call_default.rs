@@ -0,0 +1,145 @@
+// vim: tw=80
+//! An expectation can call the trait's default implementation of a method
+#![deny(warnings)]
+
+use mockall::*;
+use std::sync::Mutex;
+
+#[automock]
+trait Foo {
+    fn required(&self, x: u32) -> u32;
+    fn provided(&self, x: u32) -> u32 {
+        self.required(x) + 1
+    }
+    fn name(&self) -> &str {
+        "foo"
+    }
+    fn bump(&mut self, x: &mut u32) {
+        *x += self.required(*x);
+    }
+    fn generic<T: Clone + Send + 'static>(&self, t: T) -> Vec<T> {
+        vec![t.clone(), t]
+    }
+    fn stat(x: u32) -> u32 {
+        x * 2
+    }
+}
+
+#[automock]
+trait Bar<T: Clone + 'static> {
+    fn bar(&self, t: T) -> (T, T) {
+        (t.clone(), t)
+    }
+}
+
+static STAT_MTX: Mutex<()> = Mutex::new(());
+
+#[test]
+fn basic() {
+    let mut mock = MockFoo::new();
+    mock.expect_required().return_const(41u32);
+    mock.expect_provided().call_default();
+    assert_eq!(42, mock.provided(5));
+}
+
+#[test]
+fn calls_are_counted_and_recorded() {
+    let mut mock = MockFoo::new();
+    mock.expect_required().return_const(0u32);
+    mock.expect_provided().times(2).call_default();
+    mock.provided(1);
+    mock.provided(2);
+    assert_eq!(mock.calls_provided(), [(Some(1),), (Some(2),)]);
+    mock.checkpoint();
+}
+
+/// A call that doesn't match any expectation is recorded exactly once
+#[test]
+fn unmatched_call_is_recorded() {
+    let mut mock = MockFoo::new();
+    mock.expect_provided()
+        .with(predicate::eq(1))
+        .call_default();
+    let r = std::panic::catch_unwind(
+        std::panic::AssertUnwindSafe(|| mock.provided(2))
+    );
+    assert!(r.is_err());
+    assert_eq!(mock.calls_provided(), [(Some(2),)]);
+}
+
+/// Matchers are evaluated once per call, even when the expectation doesn't
+/// call the default implementation
+#[test]
+fn captor_captures_once() {
+    let x = Captor::new();
+    let mut mock = MockFoo::new();
+    mock.expect_provided()
+        .with(x.clone())
+        .return_const(0u32);
+    mock.provided(7);
+    assert_eq!(x.values(), [7]);
+}
+
+#[test]
+fn fallback() {
+    let mut mock = MockFoo::new();
+    mock.expect_required().return_const(1u32);
+    mock.expect_provided()
+        .with(predicate::eq(5))
+        .return_const(50u32);
+    mock.fallback_provided().call_default();
+    assert_eq!(50, mock.provided(5));
+    assert_eq!(2, mock.provided(6));
+}
+
+#[test]
+fn generic_method() {
+    let mut mock = MockFoo::new();
+    mock.expect_generic::<u8>().call_default();
+    assert_eq!(vec![3, 3], mock.generic(3u8));
+}
+
+#[test]
+fn generic_trait() {
+    let mut mock = MockBar::<i16>::new();
+    mock.expect_bar().call_default();
+    assert_eq!((-1, -1), mock.bar(-1));
+}
+
+#[test]
+fn matching() {
+    let mut mock = MockFoo::new();
+    mock.expect_required().return_const(0u32);
+    mock.expect_provided()
+        .with(predicate::eq(4))
+        .call_default();
+    mock.expect_provided()
+        .return_const(99u32);
+    assert_eq!(1, mock.provided(4));
+    assert_eq!(99, mock.provided(5));
+}
+
+#[test]
+fn return_ref() {
+    let mut mock = MockFoo::new();
+    mock.expect_name().call_default();
+    assert_eq!("foo", mock.name());
+}
+
+#[test]
+fn mutable_reference() {
+    let mut mock = MockFoo::new();
+    mock.expect_required().returning(|x| x * 10);
+    mock.expect_bump().call_default();
+    let mut x = 2;
+    mock.bump(&mut x);
+    assert_eq!(22, x);
+}
+
+#[test]
+fn static_method() {
+    let _m = STAT_MTX.lock().unwrap();
+    let ctx = MockFoo::stat_context();
+    ctx.expect().call_default();
+    assert_eq!(8, MockFoo::stat(4));
+}
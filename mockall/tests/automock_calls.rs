@@ -0,0 +1,157 @@
+// vim: tw=80
+//! Every mock method records the arguments of its calls
+#![deny(warnings)]
+
+use mockall::*;
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{Mutex, mpsc}
+};
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct NonClone(u32);
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32, s: &str);
+    fn bar(&self, x: NonClone, p: &Path, v: &[u8]) -> u32;
+    fn baz<T: Clone + Send + 'static>(&self, t: T);
+    fn bean(&mut self, x: &mut u32);
+    fn boom(&self, x: Rc<u32>);
+    fn noargs(&self);
+    fn send(&self, tx: mpsc::Sender<u32>);
+    fn stat(x: i16);
+}
+
+static STAT_MTX: Mutex<()> = Mutex::new(());
+
+#[test]
+fn checkpoint_clears_history() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo().return_const(());
+    mock.foo(1, "one");
+    mock.checkpoint();
+    assert!(mock.calls_foo().is_empty());
+}
+
+/// The call history keeps its copies of the arguments until a checkpoint
+#[test]
+fn checkpoint_releases_arguments() {
+    let mut mock = MockFoo::new();
+    mock.expect_send().return_const(());
+    let (tx, rx) = mpsc::channel();
+    mock.send(tx);
+    assert_eq!(mock.calls_send().len(), 1);
+    mock.checkpoint();
+    assert!(rx.recv().is_err());
+}
+
+#[test]
+fn generic_method() {
+    let mut mock = MockFoo::new();
+    mock.expect_baz::<u32>().return_const(());
+    mock.expect_baz::<String>().return_const(());
+    mock.baz(1u32);
+    mock.baz(String::from("two"));
+    mock.baz(3u32);
+    assert_eq!(mock.calls_baz::<u32>(), [(Some(1),), (Some(3),)]);
+    assert_eq!(mock.calls_baz::<String>(), [(Some(String::from("two")),)]);
+}
+
+#[test]
+fn in_order() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(1), predicate::always())
+        .return_const(());
+    mock.expect_foo().return_const(());
+    mock.foo(2, "two");
+    mock.foo(1, "one");
+    mock.foo(3, "three");
+    assert_eq!(mock.calls_foo(), [
+        (Some(2), Some(String::from("two"))),
+        (Some(1), Some(String::from("one"))),
+        (Some(3), Some(String::from("three")))
+    ]);
+}
+
+#[test]
+fn mutable_reference() {
+    let mut mock = MockFoo::new();
+    mock.expect_bean().returning(|x| *x += 1);
+    let mut x = 5;
+    mock.bean(&mut x);
+    assert_eq!(6, x);
+    assert_eq!(mock.calls_bean(), [(Some(5),)]);
+}
+
+#[test]
+fn no_args() {
+    let mut mock = MockFoo::new();
+    mock.expect_noargs().return_const(());
+    mock.noargs();
+    mock.noargs();
+    assert_eq!(mock.calls_noargs().len(), 2);
+}
+
+#[test]
+fn no_calls() {
+    let mock = MockFoo::new();
+    assert!(mock.calls_foo().is_empty());
+}
+
+/// Arguments that can't be cloned are recorded as None
+#[test]
+fn nonclone() {
+    let mut mock = MockFoo::new();
+    mock.expect_bar().return_const(0u32);
+    mock.bar(NonClone(1), Path::new("/tmp"), &[1, 2, 3]);
+    assert_eq!(mock.calls_bar(),
+        [(None, Some(PathBuf::from("/tmp")), Some(vec![1, 2, 3]))]);
+}
+
+/// Arguments that aren't Send are recorded as None, so the mock stays Send
+#[test]
+fn nonsend() {
+    let mut mock = MockFoo::new();
+    mock.expect_boom().return_const(());
+    mock.boom(Rc::new(42));
+    assert_eq!(mock.calls_boom(), [(None,)]);
+    std::thread::spawn(move || drop(mock)).join().unwrap();
+}
+
+#[test]
+fn static_method() {
+    let _m = STAT_MTX.lock().unwrap();
+    let ctx = MockFoo::stat_context();
+    ctx.expect().return_const(());
+    MockFoo::stat(4);
+    MockFoo::stat(-4);
+    assert_eq!(ctx.calls(), [(Some(4),), (Some(-4),)]);
+}
+
+#[test]
+fn static_method_history_is_cleared_by_context() {
+    let _m = STAT_MTX.lock().unwrap();
+    {
+        let ctx = MockFoo::stat_context();
+        ctx.expect().return_const(());
+        MockFoo::stat(1);
+    }
+    let ctx = MockFoo::stat_context();
+    assert!(ctx.calls().is_empty());
+}
+
+#[test]
+fn unmatched_calls_are_recorded() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(1), predicate::always())
+        .return_const(());
+    let r = std::panic::catch_unwind(
+        std::panic::AssertUnwindSafe(|| mock.foo(2, "two"))
+    );
+    assert!(r.is_err());
+    assert_eq!(mock.calls_foo(), [(Some(2), Some(String::from("two")))]);
+}
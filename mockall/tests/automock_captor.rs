@@ -0,0 +1,77 @@
+// vim: tw=80
+//! Captors record the arguments of the calls they match
+#![deny(warnings)]
+
+use mockall::{*, predicate::*};
+
+#[automock]
+trait Foo {
+    fn foo(&self, id: u64, name: &str) -> u32;
+    fn bar(x: Vec<u32>);
+}
+
+#[test]
+fn values() {
+    let id = Captor::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(id.clone(), always())
+        .return_const(0u32);
+    assert!(id.values().is_empty());
+    assert_eq!(id.last(), None);
+    mock.foo(1, "a");
+    mock.foo(2, "b");
+    assert_eq!(id.values(), vec![1, 2]);
+    assert_eq!(id.last(), Some(2));
+}
+
+/// Unsized arguments are captured as their owned equivalents
+#[test]
+fn unsized_arg() {
+    let name = Captor::<String>::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(always(), name.clone())
+        .return_const(0u32);
+    mock.foo(1, "alice");
+    assert_eq!(name.values(), vec![String::from("alice")]);
+}
+
+/// A captor can be combined with other predicates
+#[test]
+fn combined() {
+    let id = Captor::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(id.clone().and(gt(10)), always())
+        .return_const(1u32);
+    mock.expect_foo()
+        .return_const(2u32);
+    assert_eq!(mock.foo(5, "a"), 2);
+    assert_eq!(mock.foo(15, "a"), 1);
+    assert_eq!(id.values(), vec![5, 15]);
+}
+
+/// A captor doesn't capture anything while explaining a failed match
+#[test]
+fn mismatch() {
+    let id = Captor::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(id.clone(), eq("a"))
+        .return_const(0u32);
+    let r = std::panic::catch_unwind(move || mock.foo(5, "b"));
+    assert!(r.is_err());
+    assert_eq!(id.values(), vec![5]);
+}
+
+#[test]
+fn static_method() {
+    let x = Captor::new();
+    let ctx = MockFoo::bar_context();
+    ctx.expect()
+        .with(x.clone())
+        .return_const(());
+    MockFoo::bar(vec![1, 2]);
+    assert_eq!(x.last(), Some(vec![1, 2]));
+}
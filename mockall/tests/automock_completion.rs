@@ -0,0 +1,99 @@
+// vim: tw=80
+//! Async tests can await an expectation's completion
+#![deny(warnings)]
+
+use std::{sync::Arc, thread};
+
+use futures::{
+    FutureExt,
+    executor::{LocalPool, block_on},
+    task::LocalSpawnExt
+};
+use mockall::*;
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32);
+    fn bar(x: u32);
+}
+
+#[test]
+fn already_satisfied() {
+    let mut mock = MockFoo::new();
+    let done = mock.expect_foo()
+        .times(1)
+        .return_const(())
+        .completion();
+    mock.foo(0);
+    assert_eq!(done.now_or_never(), Some(()));
+}
+
+#[test]
+fn optional() {
+    let mut mock = MockFoo::new();
+    let done = mock.expect_foo()
+        .return_const(())
+        .completion();
+    assert_eq!(done.now_or_never(), Some(()));
+}
+
+#[test]
+fn pending() {
+    let mut mock = MockFoo::new();
+    let mut done = mock.expect_foo()
+        .times(2)
+        .return_const(())
+        .completion();
+    assert_eq!((&mut done).now_or_never(), None);
+    mock.foo(0);
+    assert_eq!((&mut done).now_or_never(), None);
+    mock.foo(1);
+    assert_eq!(done.now_or_never(), Some(()));
+}
+
+#[test]
+fn other_task() {
+    let mut pool = LocalPool::new();
+    let mut mock = MockFoo::new();
+    let done = mock.expect_foo()
+        .times(3)
+        .return_const(())
+        .completion();
+    let mock = Arc::new(mock);
+    let mock2 = mock.clone();
+    pool.spawner().spawn_local(async move {
+        for i in 0..3 {
+            mock2.foo(i);
+        }
+    }).unwrap();
+    pool.run_until(done);
+}
+
+#[test]
+fn other_thread() {
+    let mut mock = MockFoo::new();
+    let done = mock.expect_foo()
+        .times(3)
+        .return_const(())
+        .completion();
+    let mock = Arc::new(mock);
+    let mock2 = mock.clone();
+    let t = thread::spawn(move || {
+        for i in 0..3 {
+            mock2.foo(i);
+        }
+    });
+    block_on(done);
+    t.join().unwrap();
+}
+
+#[test]
+fn static_method() {
+    let ctx = MockFoo::bar_context();
+    let done = ctx.expect()
+        .times(1)
+        .return_const(())
+        .completion();
+    MockFoo::bar(0);
+    block_on(done);
+}
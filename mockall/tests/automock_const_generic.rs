@@ -0,0 +1,90 @@
+// vim: tw=80
+//! Mocking traits, structs, and methods with const generic parameters
+#![deny(warnings)]
+
+use mockall::*;
+
+#[automock]
+trait Buffer<const N: usize> {
+    fn capacity(&self) -> usize;
+    fn fill(&mut self, data: [u8; N]) -> usize;
+    fn make() -> [u8; N];
+}
+
+#[automock]
+trait Chunker {
+    fn chunk<const M: usize>(&self, data: [u8; M]) -> usize;
+    fn split<const M: usize>(data: [u8; M]) -> usize;
+}
+
+pub struct Ring<const N: usize>([u8; N]);
+#[automock]
+impl<const N: usize> Ring<N> {
+    pub fn size(&self) -> usize { unimplemented!() }
+    pub fn push(&mut self, _x: [u8; N]) { unimplemented!() }
+    pub fn with_capacity(_cap: usize) -> Self { unimplemented!() }
+}
+
+#[automock]
+trait Factory<const N: usize> {
+    fn create() -> Self;
+}
+
+#[test]
+fn trait_() {
+    let mut mock = MockBuffer::<4>::new();
+    mock.expect_capacity().return_const(4usize);
+    mock.expect_fill()
+        .withf(|data| data == &[1, 2, 3, 4])
+        .returning(|data| data.len());
+    assert_eq!(mock.capacity(), 4);
+    assert_eq!(mock.fill([1, 2, 3, 4]), 4);
+}
+
+#[test]
+fn trait_static() {
+    let ctx = MockBuffer::<2>::make_context();
+    ctx.expect().return_const([7u8; 2]);
+    assert_eq!(MockBuffer::<2>::make(), [7, 7]);
+}
+
+#[test]
+fn generic_method() {
+    let mut mock = MockChunker::new();
+    mock.expect_chunk::<2>().return_const(2usize);
+    mock.expect_chunk::<3>().return_const(3usize);
+    assert_eq!(mock.chunk([0u8; 2]), 2);
+    assert_eq!(mock.chunk([0u8; 3]), 3);
+}
+
+#[test]
+fn generic_static_method() {
+    let ctx = MockChunker::split_context();
+    ctx.expect::<5>().returning(|data| data.len());
+    assert_eq!(MockChunker::split([0u8; 5]), 5);
+}
+
+#[test]
+fn struct_() {
+    let mut mock = MockRing::<8>::new();
+    mock.expect_size().return_const(8usize);
+    mock.expect_push()
+        .withf(|x| x[0] == 1)
+        .return_const(());
+    assert_eq!(mock.size(), 8);
+    mock.push([1; 8]);
+}
+
+#[test]
+fn struct_constructor() {
+    let ctx = MockRing::<8>::with_capacity_context();
+    ctx.expect().returning(|_| MockRing::<8>::default());
+    let _mock: MockRing<8> = MockRing::<8>::with_capacity(8);
+}
+
+#[test]
+fn trait_constructor() {
+    let ctx = MockFactory::<3>::create_context();
+    ctx.expect().returning(MockFactory::<3>::default);
+    let _mock: MockFactory<3> = MockFactory::<3>::create();
+}
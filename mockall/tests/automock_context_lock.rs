@@ -0,0 +1,110 @@
+// vim: tw=80
+//! A static method's exclusive Context serializes tests that use it
+#![deny(warnings)]
+
+use std::{
+    panic,
+    sync::{Arc, atomic::{AtomicBool, Ordering}},
+    thread,
+    time::Duration
+};
+
+use mockall::*;
+
+#[automock]
+trait Foo {
+    fn foo() -> u32;
+    fn bar() -> u32;
+    fn baz() -> u32;
+}
+
+/// These two tests would race if they ran in parallel
+#[test]
+fn independent_1() {
+    let ctx = MockFoo::foo_context_exclusive();
+    ctx.expect().return_const(1u32);
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(MockFoo::foo(), 1);
+}
+
+#[test]
+fn independent_2() {
+    let ctx = MockFoo::foo_context_exclusive();
+    ctx.expect().return_const(2u32);
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(MockFoo::foo(), 2);
+}
+
+/// A thread may hold several exclusive Contexts for the same method
+#[test]
+fn reentrant() {
+    let ctx1 = MockFoo::bar_context_exclusive();
+    let ctx2 = MockFoo::bar_context_exclusive();
+    ctx1.expect().return_const(1u32);
+    assert_eq!(MockFoo::bar(), 1);
+    drop(ctx1);
+    ctx2.expect().return_const(2u32);
+    assert_eq!(MockFoo::bar(), 2);
+}
+
+/// Another thread's exclusive Context waits until this thread's have dropped
+#[test]
+fn blocks_other_threads() {
+    let ctx1 = MockFoo::baz_context_exclusive();
+    let ctx2 = MockFoo::baz_context_exclusive();
+    let acquired = Arc::new(AtomicBool::new(false));
+    let acquired2 = acquired.clone();
+    let t = thread::spawn(move || {
+        let ctx = MockFoo::baz_context_exclusive();
+        acquired2.store(true, Ordering::SeqCst);
+        ctx.expect().return_const(3u32);
+        assert_eq!(MockFoo::baz(), 3);
+    });
+    thread::sleep(Duration::from_millis(50));
+    drop(ctx1);
+    thread::sleep(Duration::from_millis(50));
+    assert!(!acquired.load(Ordering::SeqCst));
+    drop(ctx2);
+    t.join().unwrap();
+    assert!(acquired.load(Ordering::SeqCst));
+}
+
+/// An exclusive Context dropped during a panic releases the lock
+#[test]
+fn released_on_panic() {
+    #[automock]
+    trait Bean {
+        fn bean() -> u32;
+    }
+
+    let r = panic::catch_unwind(|| {
+        let ctx = MockBean::bean_context_exclusive();
+        ctx.expect().return_const(0u32);
+        panic!("oops");
+    });
+    assert!(r.is_err());
+    thread::spawn(|| {
+        let ctx = MockBean::bean_context_exclusive();
+        ctx.expect().return_const(1u32);
+        assert_eq!(MockBean::bean(), 1);
+    }).join().unwrap();
+}
+
+/// An ordinary Context doesn't take the lock, so it may be sent to another
+/// thread, even while an exclusive one exists.
+#[test]
+fn not_exclusive() {
+    #[automock]
+    trait Bean {
+        fn bean() -> u32;
+    }
+
+    let ctx1 = MockBean::bean_context_exclusive();
+    thread::spawn(|| {
+        let ctx = MockBean::bean_context();
+        ctx.expect().return_const(4u32);
+        assert_eq!(MockBean::bean(), 4);
+        thread::spawn(move || drop(ctx)).join().unwrap();
+    }).join().unwrap();
+    drop(ctx1);
+}
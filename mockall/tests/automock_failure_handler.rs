@@ -0,0 +1,132 @@
+// vim: tw=80
+//! A FailureHandler receives mock failures instead of a panic
+#![deny(warnings)]
+
+use std::{cell::RefCell, rc::Rc};
+
+use mockall::*;
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar(&self);
+}
+
+/// Install a handler that records every failure it sees
+fn record() -> (FailureHandlerGuard, Rc<RefCell<Vec<Failure>>>) {
+    let failures = Rc::new(RefCell::new(Vec::new()));
+    let failures2 = failures.clone();
+    let guard = set_failure_handler(move |f: &Failure| {
+        failures2.borrow_mut().push(f.clone());
+    });
+    (guard, failures)
+}
+
+fn kinds(failures: &RefCell<Vec<Failure>>) -> Vec<FailureKind> {
+    failures.borrow().iter().map(|f| f.kind).collect()
+}
+
+#[test]
+fn too_few_calls() {
+    let (_guard, failures) = record();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(2)
+        .return_const(0u32);
+    mock.foo(0);
+    drop(mock);
+    assert_eq!(kinds(&failures), [FailureKind::TooFewCalls]);
+    assert!(failures.borrow()[0].message.starts_with(
+        "MockFoo::foo: Expectation(<anything>) called 1 time(s) which is fewer than expected 2"));
+}
+
+/// After reporting too many calls, the mock keeps returning its value.  The
+/// failure isn't reported again when the mock is dropped.
+#[test]
+fn too_many_calls() {
+    let (_guard, failures) = record();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .return_const(42u32);
+    assert_eq!(mock.foo(0), 42);
+    assert_eq!(mock.foo(0), 42);
+    drop(mock);
+    assert_eq!(kinds(&failures), [FailureKind::TooManyCalls]);
+}
+
+#[test]
+fn sequence_violation() {
+    let (_guard, failures) = record();
+    let mut seq = Sequence::new();
+    let mut mock = MockFoo::new();
+    mock.expect_bar()
+        .times(1)
+        .return_const(())
+        .in_sequence(&mut seq);
+    mock.expect_foo()
+        .times(1)
+        .return_const(0u32)
+        .in_sequence(&mut seq);
+    mock.foo(0);
+    mock.bar();
+    assert_eq!(kinds(&failures), [FailureKind::SequenceViolation]);
+    assert!(failures.borrow()[0].message
+        .starts_with("MockFoo::foo(0): Method sequence violation"));
+}
+
+/// The mock has nothing to return, so it panics after reporting the failure
+#[test]
+fn unexpected_call() {
+    let (_guard, failures) = record();
+    let mock = MockFoo::new();
+    let r = std::panic::catch_unwind(|| mock.bar());
+    assert!(r.is_err());
+    assert_eq!(kinds(&failures), [FailureKind::UnexpectedCall]);
+    assert!(failures.borrow()[0].message
+        .starts_with("MockFoo::bar(): No matching expectation found"));
+}
+
+#[test]
+fn missing_return_value() {
+    let (_guard, failures) = record();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1);
+    let r = std::panic::catch_unwind(|| mock.foo(0));
+    assert!(r.is_err());
+    assert_eq!(kinds(&failures), [FailureKind::MissingReturnValue]);
+}
+
+/// Dropping the guard restores the previous handler
+#[test]
+#[should_panic(expected = "called 0 time(s) which is fewer than expected 1")]
+fn guard_restores() {
+    {
+        let (_guard, _failures) = record();
+    }
+    let mut mock = MockFoo::new();
+    mock.expect_bar()
+        .times(1)
+        .return_const(());
+}
+
+#[test]
+fn nested() {
+    let (_outer, outer_failures) = record();
+    {
+        let (_inner, inner_failures) = record();
+        let mut mock = MockFoo::new();
+        mock.expect_bar()
+            .times(1)
+            .return_const(());
+        drop(mock);
+        assert_eq!(kinds(&inner_failures), [FailureKind::TooFewCalls]);
+    }
+    let mut mock = MockFoo::new();
+    mock.expect_bar()
+        .never()
+        .return_const(());
+    mock.bar();
+    assert_eq!(kinds(&outer_failures), [FailureKind::TooManyCalls]);
+}
@@ -4,9 +4,10 @@
 use mockall::*;
 use std::sync::Mutex;
 
+// Use the "C-unwind" ABI so the mock function may panic without aborting.
 #[automock]
 mod ffi {
-    extern "C" {
+    extern "C-unwind" {
         pub(super) fn foo(x: u32) -> i64;
     }
 }
@@ -47,6 +48,6 @@ fn c_abi() {
     let _m = FOO_MTX.lock();
     let ctx = mock_ffi::foo_context();
     ctx.expect().returning(i64::from);
-    let p: unsafe extern "C" fn(u32) -> i64 = mock_ffi::foo;
+    let p: unsafe extern "C-unwind" fn(u32) -> i64 = mock_ffi::foo;
     assert_eq!(42, unsafe{p(42)});
 }
@@ -13,6 +13,7 @@ use std::{
     task::{Context, Poll},
 };
 
+#[allow(dead_code)]
 struct Foo<T>(T);
 
 #[automock]
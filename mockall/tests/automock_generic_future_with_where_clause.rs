@@ -9,6 +9,7 @@
 
 use mockall::*;
 
+#[allow(dead_code)]
 struct Foo<T, V>((T, V));
 trait MyTrait {
     type Item;
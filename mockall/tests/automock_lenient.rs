@@ -0,0 +1,139 @@
+// vim: tw=80
+//! Lenient mocks return default values for calls that don't match any
+//! expectation
+#![deny(warnings)]
+
+use mockall::*;
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct NonDefault(u32);
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar(&self) -> &String;
+    fn baz(&mut self) -> &mut Vec<u32>;
+    fn bean(&self) -> NonDefault;
+    fn boom<T: Default + 'static>(&self, t: T) -> T;
+    fn noret(&self, x: u32);
+}
+
+#[automock]
+trait Bar {
+    fn bar(&self) -> i16;
+}
+
+mock! {
+    Baz {
+        fn baz(&self) -> Option<u8>;
+    }
+    impl Bar for Baz {
+        fn bar(&self) -> i16;
+    }
+}
+
+#[test]
+fn checkpoint_keeps_lenience() {
+    let mut mock = MockFoo::new_lenient();
+    mock.expect_foo().times(1).return_const(5u32);
+    assert_eq!(5, mock.foo(1));
+    mock.checkpoint();
+    assert_eq!(0, mock.foo(1));
+}
+
+#[test]
+fn checkpoint_clears_fallback() {
+    let mut mock = MockFoo::new();
+    mock.fallback_foo().return_const(6u32);
+    mock.checkpoint();
+    let r = std::panic::catch_unwind(
+        std::panic::AssertUnwindSafe(|| mock.foo(1))
+    );
+    assert!(r.is_err());
+}
+
+#[test]
+fn expectations_take_precedence() {
+    let mut mock = MockFoo::new_lenient();
+    mock.expect_foo()
+        .with(predicate::eq(4))
+        .return_const(42u32);
+    assert_eq!(42, mock.foo(4));
+    assert_eq!(0, mock.foo(5));
+}
+
+#[test]
+fn fallback() {
+    let mut mock = MockFoo::new();
+    mock.fallback_foo().returning(|x| x + 1);
+    mock.expect_foo()
+        .with(predicate::eq(4))
+        .return_const(42u32);
+    assert_eq!(42, mock.foo(4));
+    assert_eq!(6, mock.foo(5));
+}
+
+#[test]
+fn fallback_overrides_lenience() {
+    let mut mock = MockFoo::new_lenient();
+    mock.fallback_bean().returning(|| NonDefault(7));
+    assert_eq!(NonDefault(7), mock.bean());
+}
+
+#[test]
+fn generic_method() {
+    let mock = MockFoo::new_lenient();
+    assert_eq!(0u32, mock.boom(5u32));
+    assert_eq!("", mock.boom(String::from("five")));
+}
+
+#[test]
+#[should_panic(expected =
+    "MockFoo::bean: Returning default values requires the \"nightly\" feature")]
+#[cfg(not(feature = "nightly"))]
+fn non_default() {
+    let mock = MockFoo::new_lenient();
+    mock.bean();
+}
+
+#[test]
+#[should_panic(expected =
+    "MockFoo::bean: Can only return default values for types that impl std::Default")]
+#[cfg(feature = "nightly")]
+fn non_default() {
+    let mock = MockFoo::new_lenient();
+    mock.bean();
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo(1): No matching expectation found")]
+fn not_lenient() {
+    let mock = MockFoo::new();
+    mock.foo(1);
+}
+
+#[test]
+fn return_ref() {
+    let mock = MockFoo::new_lenient();
+    assert_eq!("", mock.bar());
+}
+
+#[test]
+fn return_refmut() {
+    let mut mock = MockFoo::new_lenient();
+    mock.baz().push(5);
+    assert_eq!(&[5], &mock.baz()[..]);
+}
+
+#[test]
+fn return_unit() {
+    let mock = MockFoo::new_lenient();
+    mock.noret(1);
+}
+
+#[test]
+fn trait_impl() {
+    let mock = MockBaz::new_lenient();
+    assert_eq!(None, mock.baz());
+    assert_eq!(0, mock.bar());
+}
@@ -0,0 +1,116 @@
+// vim: tw=80
+//! Failure messages should say where the expectation was created.
+#![deny(warnings)]
+
+use mockall::*;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar(&self);
+    fn baz(x: u32);
+    fn bean<T: 'static>(t: T);
+}
+
+/// Run `f`, which should panic, and return its panic message.
+fn panic_msg<F: FnOnce()>(f: F) -> String {
+    let e = catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+    match e.downcast::<String>() {
+        Ok(s) => *s,
+        Err(e) => e.downcast_ref::<&str>().unwrap().to_string()
+    }
+}
+
+/// The suffix of a failure message about an expectation created at `line` of
+/// this file.
+fn set_at(line: u32) -> String {
+    format!("(expectation set at {}:{}:", file!(), line)
+}
+
+#[test]
+fn too_few() {
+    let mut line = 0;
+    let msg = panic_msg(|| {
+        let mut mock = MockFoo::new();
+        line = line!(); mock.expect_foo()
+            .times(1)
+            .return_const(0u32);
+    });
+    assert!(msg.contains(&set_at(line)), "{msg}");
+}
+
+#[test]
+fn too_many() {
+    let mut line = 0;
+    let msg = panic_msg(|| {
+        let mut mock = MockFoo::new();
+        line = line!(); mock.expect_bar()
+            .times(1)
+            .return_const(());
+        mock.bar();
+        mock.bar();
+    });
+    assert!(msg.contains(&set_at(line)), "{msg}");
+}
+
+#[test]
+fn sequence() {
+    let mut line = 0;
+    let msg = panic_msg(|| {
+        let mut seq = Sequence::new();
+        let mut mock = MockFoo::new();
+        mock.expect_foo()
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(0u32);
+        line = line!(); mock.expect_bar()
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(());
+        mock.bar();
+    });
+    assert!(msg.contains(&set_at(line)), "{msg}");
+}
+
+#[test]
+fn fallback() {
+    let mut line = 0;
+    let msg = panic_msg(|| {
+        let mut mock = MockFoo::new();
+        line = line!(); mock.fallback_foo()
+            .never();
+        mock.foo(1);
+    });
+    assert!(msg.contains(&set_at(line)), "{msg}");
+}
+
+#[test]
+fn static_method() {
+    let mut line = 0;
+    let msg = panic_msg(|| {
+        let ctx = MockFoo::baz_context();
+        line = line!(); ctx.expect()
+            .times(1)
+            .return_const(());
+        if false {
+            MockFoo::baz(0);
+        }
+    });
+    assert!(msg.contains(&set_at(line)), "{msg}");
+}
+
+#[test]
+fn generic_static_method() {
+    let mut line = 0;
+    let msg = panic_msg(|| {
+        let ctx = MockFoo::bean_context();
+        line = line!(); ctx.expect::<u32>()
+            .times(1)
+            .return_const(());
+        if false {
+            MockFoo::bean(0u32);
+        }
+    });
+    assert!(msg.contains(&set_at(line)), "{msg}");
+}
@@ -0,0 +1,120 @@
+// vim: tw=80
+//! When no expectation matches a call, the panic message should explain why
+//! each one didn't.
+#![deny(warnings)]
+
+use mockall::*;
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32, y: &str) -> u32;
+    fn bar(&mut self, x: u32) -> &mut u32;
+    fn baz(x: u32);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo(5, \"b\"): No matching expectation found
+Expectation 0: MockFoo::foo: Expectation(var == 4, var == \"a\") does not match:
+    x: failed
+        var == 4
+        └── var: 5
+    y: failed
+        var == \"a\"
+        └── var: \"b\"
+Expectation 1: MockFoo::foo: Expectation((var < 10 && var > 6), true) does not match:
+    x: failed
+        (var < 10 && var > 6)
+        └── var > 6
+            └── var: 5
+    y: passed")]
+fn predicates() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(4), predicate::eq("a"))
+        .return_const(0u32);
+    mock.expect_foo()
+        .with(predicate::lt(10).and(predicate::gt(6)), predicate::always())
+        .return_const(0u32);
+    mock.foo(5, "b");
+}
+
+#[test]
+#[should_panic(expected = "Expectation 1: MockFoo::foo: Expectation(<function>) does not match:
+    <function> returned false")]
+fn function() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(4), predicate::always())
+        .return_const(0u32);
+    mock.expect_foo()
+        .withf(|x, _| *x == 4)
+        .return_const(0u32);
+    mock.foo(5, "b");
+}
+
+/// Explaining a mismatch doesn't call the function again
+#[test]
+fn function_called_once() {
+    let n = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let n2 = n.clone();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .withf(move |x, _| {
+            n2.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            *x == 4
+        }).return_const(0u32);
+    let r = std::panic::catch_unwind(
+        std::panic::AssertUnwindSafe(|| mock.foo(5, "b"))
+    );
+    assert!(r.is_err());
+    assert_eq!(1, n.load(std::sync::atomic::Ordering::Relaxed));
+}
+
+/// Expectations that match, but have been used up, are reported too
+#[test]
+#[should_panic(expected = "Expectation 1: MockFoo::foo: Expectation(<anything>) matches, but has already been called 1 time(s)")]
+fn saturated() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .with(predicate::eq(4), predicate::always())
+        .return_const(0u32);
+    mock.expect_foo()
+        .times(1)
+        .return_const(0u32);
+    mock.foo(5, "b");
+    mock.foo(5, "b");
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::bar(5): No matching expectation found
+Expectation 0: MockFoo::bar: Expectation(var == 4) does not match:
+    x: failed")]
+fn return_refmut() {
+    let mut mock = MockFoo::new();
+    mock.expect_bar()
+        .with(predicate::eq(4))
+        .return_var(0u32);
+    mock.bar(5);
+}
+
+#[test]
+#[should_panic(expected = "Fallback: MockFoo::foo: Expectation(var == 4, true) does not match")]
+fn fallback() {
+    let mut mock = MockFoo::new();
+    mock.fallback_foo()
+        .with(predicate::eq(4), predicate::always())
+        .return_const(0u32);
+    mock.foo(5, "b");
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::baz(5): No matching expectation found
+Expectation 0: MockFoo::baz: Expectation(var == 4) does not match:
+    x: failed")]
+fn static_method() {
+    let ctx = MockFoo::baz_context();
+    ctx.expect()
+        .with(predicate::eq(4))
+        .return_const(());
+    MockFoo::baz(5);
+}
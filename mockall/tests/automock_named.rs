@@ -0,0 +1,102 @@
+// vim: tw=80
+//! Expectations may be labeled, to identify them in failure messages
+#![deny(warnings)]
+
+use mockall::*;
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar(&self);
+    fn baz(x: u32);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(<function>) named \"retry after timeout\" called 0 time(s) which is fewer than expected 1")]
+fn too_few() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .withf(|x| *x == 4)
+        .times(1)
+        .named("retry after timeout")
+        .return_const(0u32);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(<anything>) named \"first\" called 2 times which is more than the expected 1")]
+fn too_many() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .named("first")
+        .return_const(0u32);
+    mock.foo(1);
+    mock.foo(2);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(<anything>) named \"no value\"")]
+fn no_return_value() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .named("no value");
+    mock.foo(1);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::bar() named \"second\": Method sequence violation")]
+fn sequence() {
+    let mut seq = Sequence::new();
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .times(1)
+        .named("first")
+        .in_sequence(&mut seq)
+        .return_const(0u32);
+    mock.expect_bar()
+        .times(1)
+        .named("second")
+        .in_sequence(&mut seq)
+        .return_const(());
+    mock.bar();
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::bar() named \"second\": Method sequence violation: MockFoo::foo: Expectation(<anything>) named \"first\" must be called before MockFoo::bar: Expectation(<anything>) named \"second\"")]
+fn after() {
+    let mut mock = MockFoo::new();
+    let first = mock.expect_foo()
+        .times(1)
+        .named("first")
+        .return_const(0u32)
+        .handle();
+    mock.expect_bar()
+        .times(1)
+        .named("second")
+        .after(&first)
+        .return_const(());
+    mock.bar();
+}
+
+#[test]
+#[should_panic(expected = "Expectation 0: MockFoo::foo: Expectation(<function>) named \"four\" does not match")]
+fn mismatch_report() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .withf(|x| *x == 4)
+        .named("four")
+        .return_const(0u32);
+    mock.foo(5);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::baz: Expectation(<anything>) named \"static\" called 2 times which is more than the expected 1")]
+fn static_method() {
+    let ctx = MockFoo::baz_context();
+    ctx.expect()
+        .times(1)
+        .named(String::from("static"))
+        .return_const(());
+    MockFoo::baz(1);
+    MockFoo::baz(2);
+}
@@ -0,0 +1,108 @@
+// vim: tw=80
+//! Mock a module that contains other modules
+#![deny(warnings)]
+
+use mockall::*;
+use mockall_double::double;
+use std::sync::Mutex;
+
+pub struct Response(pub u32);
+
+#[automock]
+pub mod api {
+    use super::Response;
+
+    pub fn version() -> u32 { unimplemented!() }
+
+    pub mod v1 {
+        use super::super::Response;
+
+        pub fn get(_id: u32) -> Response { unimplemented!() }
+
+        pub mod admin {
+            pub fn reset() { unimplemented!() }
+        }
+    }
+
+    pub mod v2 {
+        pub fn get(_id: u32, _verbose: bool) -> super::Response {
+            unimplemented!()
+        }
+    }
+
+    #[cfg(any())]
+    pub mod disabled {
+        pub fn never() -> NoSuchType { unimplemented!() }
+    }
+
+    pub fn latest() -> Response { unimplemented!() }
+}
+
+#[double]
+use api as client;
+
+// Ensure we can still use the original functions
+pub fn normal_usage() {
+    api::version();
+    api::v1::get(1);
+    api::v1::admin::reset();
+    api::v2::get(1, true);
+}
+
+// mock_api::checkpoint affects every function, so the tests must not run in
+// parallel
+static MTX: Mutex<()> = Mutex::new(());
+
+/// Code under test, written against the real module's paths
+fn fetch(id: u32) -> u32 {
+    if client::version() >= 2 {
+        client::v2::get(id, false).0
+    } else {
+        client::v1::get(id).0
+    }
+}
+
+#[test]
+fn nested() {
+    let _m = MTX.lock();
+    let ctx = mock_api::v1::get_context();
+    ctx.expect()
+        .with(predicate::eq(5))
+        .returning(|id| Response(id * 2));
+    assert_eq!(10, mock_api::v1::get(5).0);
+}
+
+#[test]
+fn doubly_nested() {
+    let _m = MTX.lock();
+    let ctx = mock_api::v1::admin::reset_context();
+    ctx.expect()
+        .times(1)
+        .return_const(());
+    mock_api::v1::admin::reset();
+}
+
+#[test]
+fn doubled() {
+    let _m = MTX.lock();
+    let version_ctx = mock_api::version_context();
+    version_ctx.expect()
+        .return_const(2u32);
+    let get_ctx = mock_api::v2::get_context();
+    get_ctx.expect()
+        .withf(|_, verbose| !verbose)
+        .returning(|id, _| Response(id + 100));
+    assert_eq!(101, fetch(1));
+}
+
+/// The parent module's checkpoint also checkpoints its children
+#[test]
+#[should_panic(expected = "reset: Expectation(<anything>) called 0 time(s) which is fewer than expected 1")]
+fn checkpoint() {
+    let _m = MTX.lock();
+    let ctx = mock_api::v1::admin::reset_context();
+    ctx.expect()
+        .times(1)
+        .return_const(());
+    mock_api::checkpoint();
+}
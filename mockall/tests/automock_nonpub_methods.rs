@@ -3,7 +3,6 @@
 //! "dead_code" warnings.  Such methods are often private helpers used only by
 //! other public methods.
 #[deny(dead_code)]
-
 pub mod mymod {
     use mockall::automock;
 
@@ -16,10 +16,18 @@ impl<'nss> NonStaticStruct<'nss> {
     pub fn bar() -> i64{
         42
     }
-    // XXX Constructors aren't yet supported for non-static Structs
-    //pub fn new(x: &'nss i32) -> Self {
-        //NonStaticStruct{x}
-    //}
+    pub fn new(_x: &'nss i32) -> Self {
+        unimplemented!()
+    }
+    pub fn get(&self) -> &'nss i32 {
+        self._x
+    }
+    pub fn set(&mut self, x: &'nss i32) {
+        self._x = x;
+    }
+    pub fn choose(&self, _x: &'nss i32) -> &'nss i32 {
+        unimplemented!()
+    }
 }
 
 #[test]
@@ -51,3 +59,42 @@ fn static_method() {
     let x = 42i8;
     has_lt(&x);
 }
+
+#[test]
+fn constructor() {
+    let ctx = MockNonStaticStruct::new_context();
+    ctx.expect()
+        .returning(|_| MockNonStaticStruct::default());
+    let x = 42i32;
+    let _mock = MockNonStaticStruct::new(&x);
+}
+
+/// Methods may return references with the struct's lifetime.  The Expectation
+/// stores them as 'static.
+#[test]
+fn return_struct_lifetime() {
+    let mut mock = MockNonStaticStruct::default();
+    mock.expect_get()
+        .return_const(&5);
+    assert_eq!(5, *mock.get());
+}
+
+#[test]
+fn arg_with_struct_lifetime() {
+    let x = 42i32;
+    let mut mock = MockNonStaticStruct::default();
+    mock.expect_set()
+        .withf(|x| *x == 42)
+        .return_const(());
+    mock.set(&x);
+}
+
+/// The same lifetime may appear in both the arguments and the return type
+#[test]
+fn arg_and_return_with_struct_lifetime() {
+    let x = 42i32;
+    let mut mock = MockNonStaticStruct::default();
+    mock.expect_choose()
+        .returning(|x| if *x > 0 { &1 } else { &-1 });
+    assert_eq!(1, *mock.choose(&x));
+}
@@ -0,0 +1,105 @@
+// vim: tw=80
+//! Mock structs and traits whose lifetime parameters have bounds, or appear
+//! in their methods' signatures
+#![deny(warnings)]
+
+use mockall::*;
+
+pub struct Parser<'a, 'b: 'a> {
+    input: &'a str,
+    _delim: &'b str
+}
+
+#[automock]
+impl<'a, 'b: 'a> Parser<'a, 'b> {
+    pub fn new(_input: &'a str, _delim: &'b str) -> Self {
+        unimplemented!()
+    }
+    pub fn peek(&self) -> &'a str {
+        self.input
+    }
+    pub fn split(&self, _delim: &'b str) -> Option<&'a str> {
+        unimplemented!()
+    }
+}
+
+pub struct Cursor<'a, 'b> {
+    _a: &'a str,
+    _b: &'b str
+}
+
+#[automock]
+impl<'a, 'b> Cursor<'a, 'b> where 'b: 'a {
+    pub fn advance(&mut self, _by: &'b usize) -> &'a str {
+        unimplemented!()
+    }
+}
+
+pub trait Source<'a> {
+    fn next_token(&mut self) -> Option<&'a str>;
+}
+
+#[automock]
+pub trait Sink<'a> {
+    fn last(&self) -> Option<&'a str>;
+}
+
+mock! {
+    pub Lexer<'a> {}
+    impl<'a> Source<'a> for Lexer<'a> {
+        fn next_token(&mut self) -> Option<&'a str>;
+    }
+}
+
+#[test]
+fn bounded() {
+    let input = String::from("a,b");
+    let delim = String::from(",");
+    let ctx = MockParser::new_context();
+    ctx.expect()
+        .returning(|_, _| {
+            let mut mock = MockParser::default();
+            mock.expect_peek()
+                .return_const("a");
+            mock.expect_split()
+                .withf(|d| d == ",")
+                .return_const(Some("b"));
+            mock
+        });
+    let mock = MockParser::new(&input, &delim);
+    assert_eq!("a", mock.peek());
+    assert_eq!(Some("b"), mock.split(&delim));
+}
+
+#[test]
+fn where_clause() {
+    let by = 2;
+    let mut mock = MockCursor::default();
+    mock.expect_advance()
+        .returning(|by| &"abc"[*by..]);
+    assert_eq!("c", mock.advance(&by));
+}
+
+#[test]
+fn trait_impl() {
+    let mut mock = MockLexer::default();
+    let mut seq = Sequence::new();
+    mock.expect_next_token()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(Some("x"));
+    mock.expect_next_token()
+        .times(1)
+        .in_sequence(&mut seq)
+        .return_const(None);
+    assert_eq!(Some("x"), mock.next_token());
+    assert_eq!(None, mock.next_token());
+}
+
+#[test]
+fn generic_trait() {
+    let mut mock = MockSink::new();
+    mock.expect_last()
+        .return_const(Some("y"));
+    assert_eq!(Some("y"), mock.last());
+}
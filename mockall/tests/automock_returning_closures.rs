@@ -0,0 +1,97 @@
+// vim: tw=80
+//! Methods that return closures, or take closures involving associated types
+#![deny(warnings)]
+#![allow(clippy::type_complexity)]
+
+use mockall::*;
+
+#[automock(type Callback = Box<dyn Fn(u32) -> u32 + Send>;)]
+trait Registry {
+    type Callback;
+    fn get(&self, name: &str) -> Self::Callback;
+    fn wrap(&self, f: Self::Callback) -> Self::Callback;
+    fn map(&self, f: Box<dyn Fn(Self::Callback) -> u32>) -> u32;
+    fn boxed(&self) -> Box<dyn Fn(u32) -> u32>;
+    fn boxed_mut(&self) -> Box<dyn FnMut(&str) -> String + Send>;
+    fn adder(&self, x: u32) -> impl Fn(u32) -> u32;
+    fn maybe(&self) -> Option<Box<dyn Fn(u32) -> u32>>;
+    fn by_ref(&self) -> &dyn Fn(u32) -> u32;
+    fn make() -> Box<dyn Fn(u32) -> u32 + Send>;
+}
+
+#[test]
+fn associated_type() {
+    let mut mock = MockRegistry::new();
+    mock.expect_get()
+        .withf(|name| name == "inc")
+        .returning(|_| Box::new(|x| x + 1));
+    assert_eq!(mock.get("inc")(1), 2);
+}
+
+#[test]
+fn associated_type_argument() {
+    let mut mock = MockRegistry::new();
+    mock.expect_wrap()
+        .returning(|f| Box::new(move |x| f(x) * 2));
+    mock.expect_map()
+        .returning(|f| f(Box::new(|x| x + 1)));
+    assert_eq!(mock.wrap(Box::new(|x| x + 1))(1), 4);
+    assert_eq!(mock.map(Box::new(|cb| cb(5))), 6);
+}
+
+#[test]
+fn boxed() {
+    let mut mock = MockRegistry::new();
+    mock.expect_boxed()
+        .returning(|| Box::new(|x| x + 2));
+    assert_eq!(mock.boxed()(1), 3);
+}
+
+#[test]
+fn boxed_fn_mut() {
+    let mut mock = MockRegistry::new();
+    mock.expect_boxed_mut()
+        .returning(|| {
+            let mut n = 0;
+            Box::new(move |s| {
+                n += 1;
+                format!("{s}{n}")
+            })
+        });
+    let mut f = mock.boxed_mut();
+    assert_eq!(f("x"), "x1");
+    assert_eq!(f("y"), "y2");
+}
+
+#[test]
+fn impl_fn() {
+    let mut mock = MockRegistry::new();
+    mock.expect_adder()
+        .returning(|y| Box::new(move |x| x + y));
+    assert_eq!(mock.adder(5)(1), 6);
+}
+
+#[test]
+fn nested() {
+    let mut mock = MockRegistry::new();
+    mock.expect_maybe()
+        .returning(|| Some(Box::new(|x| x * 2)));
+    assert_eq!(mock.maybe().unwrap()(4), 8);
+}
+
+#[test]
+fn reference() {
+    let mut mock = MockRegistry::new();
+    let f: Box<dyn Fn(u32) -> u32 + Send + Sync> = Box::new(|x| x + 3);
+    mock.expect_by_ref()
+        .return_const(f);
+    assert_eq!(mock.by_ref()(1), 4);
+}
+
+#[test]
+fn static_method() {
+    let ctx = MockRegistry::make_context();
+    ctx.expect()
+        .returning(|| Box::new(|x| x * 3));
+    assert_eq!(MockRegistry::make()(2), 6);
+}
@@ -0,0 +1,86 @@
+// vim: tw=80
+//! An expectation can return a scripted sequence of values
+#![deny(warnings)]
+
+use mockall::*;
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar(&self) -> String;
+    fn baz() -> u32;
+}
+
+#[test]
+fn ok() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .returning_values(vec![1u32, 2, 3]);
+    assert_eq!(mock.foo(0), 1);
+    assert_eq!(mock.foo(0), 2);
+    assert_eq!(mock.foo(0), 3);
+}
+
+/// The values may be converted into the return type
+#[test]
+fn into() {
+    let mut mock = MockFoo::new();
+    mock.expect_bar()
+        .returning_values(["a", "b"]);
+    assert_eq!(mock.bar(), "a");
+    assert_eq!(mock.bar(), "b");
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(<anything>) called 4 times which is more than the expected 3")]
+fn too_many() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .returning_values(1u32..=3);
+    mock.foo(0);
+    mock.foo(0);
+    mock.foo(0);
+    mock.foo(0);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(<anything>) called 2 time(s) which is fewer than expected 3")]
+fn too_few() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .returning_values(1u32..=3);
+    mock.foo(0);
+    mock.foo(0);
+}
+
+/// Calling `times` afterwards can allow fewer calls
+#[test]
+fn times_afterwards() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .returning_values(vec![1u32, 2, 3])
+        .times(..=3);
+    assert_eq!(mock.foo(0), 1);
+}
+
+/// Once an expectation's values are used up, later ones take over
+#[test]
+fn exhausted() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .returning_values(vec![1u32, 2]);
+    mock.expect_foo()
+        .return_const(99u32);
+    assert_eq!(mock.foo(0), 1);
+    assert_eq!(mock.foo(0), 2);
+    assert_eq!(mock.foo(0), 99);
+}
+
+#[test]
+fn static_method() {
+    let ctx = MockFoo::baz_context();
+    ctx.expect()
+        .returning_values(vec![4u32, 5]);
+    assert_eq!(MockFoo::baz(), 4);
+    assert_eq!(MockFoo::baz(), 5);
+}
@@ -0,0 +1,202 @@
+// vim: tw=80
+//! A shared handle lets a test keep configuring a mock after handing a clone
+//! of it to the code under test
+#![deny(warnings)]
+
+use std::thread;
+
+use mockall::*;
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32) -> u32;
+    fn bar(&mut self, x: u32);
+    fn baz<T: 'static>(&self, t: T) -> u32 where Self: Sized;
+}
+
+/// Some long-lived object that owns its dependency
+struct Widget {
+    foo: Box<dyn Foo + Send>,
+}
+
+impl Widget {
+    fn step(&mut self, x: u32) -> u32 {
+        self.foo.bar(x);
+        self.foo.foo(x)
+    }
+}
+
+#[test]
+fn stepwise() {
+    let handle = MockFoo::shared();
+    let mut widget = Widget{foo: Box::new(handle.clone())};
+
+    handle.lock().expect_bar()
+        .with(predicate::eq(1))
+        .times(1)
+        .return_const(());
+    handle.lock().expect_foo()
+        .times(1)
+        .return_const(10u32);
+    assert_eq!(widget.step(1), 10);
+    handle.lock().checkpoint();
+
+    handle.lock().expect_bar()
+        .with(predicate::eq(2))
+        .times(1)
+        .return_const(());
+    handle.lock().expect_foo()
+        .times(1)
+        .return_const(20u32);
+    assert_eq!(widget.step(2), 20);
+}
+
+#[test]
+fn clones_share_expectations() {
+    let handle = MockFoo::shared();
+    let clone = handle.clone();
+    handle.lock().expect_foo()
+        .times(2)
+        .returning(|x| x + 1);
+    assert_eq!(handle.foo(1), 2);
+    assert_eq!(clone.foo(2), 3);
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(<anything>) called 0 time(s) which is fewer than expected 1")]
+fn checkpoint() {
+    let handle = MockFoo::shared();
+    let _widget = Widget{foo: Box::new(handle.clone())};
+    handle.lock().expect_foo()
+        .times(1)
+        .return_const(0u32);
+    handle.lock().checkpoint();
+}
+
+#[test]
+fn from_mock() {
+    let mut mock = MockFoo::new();
+    mock.expect_foo()
+        .return_const(5u32);
+    let handle = SharedMockFoo::from(mock);
+    assert_eq!(handle.foo(0), 5);
+}
+
+#[test]
+fn generic_method() {
+    let handle = MockFoo::shared();
+    handle.lock().expect_baz::<i16>()
+        .return_const(16u32);
+    assert_eq!(handle.baz(0i16), 16);
+}
+
+#[test]
+fn other_thread() {
+    let handle = MockFoo::shared();
+    handle.lock().expect_foo()
+        .returning(|x| x * 2);
+    let mut widget = Widget{foo: Box::new(handle.clone())};
+    handle.lock().expect_bar()
+        .return_const(());
+    let r = thread::spawn(move || widget.step(21)).join().unwrap();
+    assert_eq!(r, 42);
+}
+
+/// Expectations are still verified once the last handle drops
+#[test]
+#[should_panic(expected = "MockFoo::bar: Expectation(<anything>) called 0 time(s) which is fewer than expected 1")]
+fn verified_on_drop() {
+    let handle = MockFoo::shared();
+    let widget = Widget{foo: Box::new(handle.clone())};
+    handle.lock().expect_bar()
+        .times(1)
+        .return_const(());
+    drop(handle);
+    drop(widget);
+}
+
+mod static_method {
+    use super::*;
+
+    #[automock]
+    trait Bar {
+        fn make(x: u32) -> u32;
+        fn bar(&self) -> u32;
+    }
+
+    fn build<B: Bar>(x: u32) -> u32 {
+        B::make(x)
+    }
+
+    #[test]
+    fn forwarded() {
+        let ctx = MockBar::make_context();
+        ctx.expect().returning(|x| x + 1);
+        assert_eq!(build::<SharedMockBar>(1), 2);
+        let handle = MockBar::shared();
+        handle.lock().expect_bar().return_const(3u32);
+        assert_eq!(handle.bar(), 3);
+    }
+}
+
+mod clone {
+    use super::*;
+
+    mock! {
+        Baz {}
+        impl Clone for Baz {
+            fn clone(&self) -> Self;
+        }
+        impl Foo for Baz {
+            fn foo(&self, x: u32) -> u32;
+            fn bar(&mut self, x: u32);
+            fn baz<T: 'static>(&self, t: T) -> u32 where Self: Sized;
+        }
+    }
+
+    /// The handle's Clone clones the handle, not the mock
+    #[test]
+    fn handle_clone() {
+        let handle = MockBaz::shared();
+        handle.lock().expect_foo()
+            .return_const(4u32);
+        assert_eq!(handle.clone().foo(0), 4);
+    }
+}
+
+/// The handle doesn't implement a trait whose methods it can't all forward,
+/// but it can still reach the mock
+mod unshareable {
+    use super::*;
+
+    #[automock]
+    trait Qux {
+        fn qux(&self) -> &u32;
+    }
+
+    #[test]
+    fn through_lock() {
+        let handle = MockQux::shared();
+        handle.lock().expect_qux().return_const(5u32);
+        assert_eq!(*handle.lock().qux(), 5);
+    }
+}
+
+/// A mock with only inherent methods gets a handle, too
+mod inherent {
+    use super::*;
+
+    mock! {
+        Thing {
+            fn thing(&self) -> u32;
+        }
+    }
+
+    #[test]
+    fn through_lock() {
+        let handle = MockThing::shared();
+        let clone = handle.clone();
+        handle.lock().expect_thing().return_const(6u32);
+        assert_eq!(clone.lock().thing(), 6);
+    }
+}
@@ -0,0 +1,77 @@
+// vim: tw=80
+//! Mocking an impl block whose self type is defined in another module
+#![deny(warnings)]
+
+use mockall::*;
+
+pub mod a {
+    pub struct Foo<T>(T);
+    pub struct Bar;
+    pub trait Baz {
+        fn baz(&self, x: u32) -> u32;
+    }
+    pub mod b {
+        #[derive(Clone, Debug, Eq, PartialEq)]
+        pub struct Q(pub u32);
+    }
+}
+
+pub mod inherent {
+    #[mockall::automock]
+    impl<T: 'static> crate::a::Foo<T> {
+        pub fn foo(&self, _t: T) -> super::a::b::Q { unimplemented!() }
+        pub fn make(_x: u32) -> Self { unimplemented!() }
+    }
+}
+
+pub mod trait_ {
+    use super::*;
+
+    #[automock]
+    impl a::Baz for crate::a::Bar {
+        fn baz(&self, _x: u32) -> u32 { unimplemented!() }
+    }
+}
+
+mod relative {
+    use super::*;
+
+    #[automock]
+    impl super::a::Bar {
+        pub fn bar(&self) -> u32 { unimplemented!() }
+    }
+}
+
+#[test]
+fn inherent_method() {
+    let mut mock = inherent::MockFoo::<u8>::new();
+    mock.expect_foo()
+        .with(predicate::eq(1))
+        .return_const(a::b::Q(3));
+    assert_eq!(mock.foo(1), a::b::Q(3));
+}
+
+#[test]
+fn constructor() {
+    let ctx = inherent::MockFoo::<u16>::make_context();
+    ctx.expect().returning(|_| inherent::MockFoo::default());
+    let _mock = inherent::MockFoo::<u16>::make(5);
+}
+
+#[test]
+fn trait_method() {
+    use a::Baz;
+
+    let mut mock = trait_::MockBar::new();
+    mock.expect_baz()
+        .returning(|x| x + 1);
+    assert_eq!(mock.baz(4), 5);
+}
+
+#[test]
+fn super_path() {
+    let mut mock = relative::MockBar::new();
+    mock.expect_bar()
+        .return_const(7u32);
+    assert_eq!(mock.bar(), 7);
+}
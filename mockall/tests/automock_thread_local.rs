@@ -0,0 +1,137 @@
+// vim: tw=80
+//! Static methods and module functions may store their expectations per
+//! thread
+#![deny(warnings)]
+
+use std::{sync::mpsc, thread};
+
+use mockall::*;
+
+#[automock(static_expectations = "thread_local")]
+trait Foo {
+    fn foo(x: u32) -> u32;
+    fn bar<T: 'static>(x: T) -> u32;
+}
+
+pub struct Bar;
+#[automock(static_expectations = "thread_local")]
+impl Bar {
+    pub fn bar() -> u32 { unimplemented!() }
+}
+
+#[automock(static_expectations = "thread_local")]
+mod m {
+    pub fn baz(_x: u32) -> u32 { unimplemented!() }
+}
+
+// Use the "C-unwind" ABI so the mock function may panic without aborting.
+#[automock(static_expectations = "thread_local")]
+mod ffi {
+    extern "C-unwind" {
+        pub(super) fn qux(x: u32) -> u32;
+    }
+}
+
+// Ensure we can still use the original mocked functions
+pub fn normal_usage() {
+    m::baz(42);
+    unsafe {
+        ffi::qux(42);
+    }
+}
+
+#[automock(static_expectations = "thread_local", type T = u32;)]
+trait Assoc {
+    type T;
+    fn assoc() -> Self::T;
+}
+
+/// Two threads may hold Contexts for the same method at once, each with its
+/// own expectations
+#[test]
+fn concurrent() {
+    let (tx, rx) = mpsc::channel();
+    let ctx = MockFoo::foo_context();
+    ctx.expect().returning(|x| x + 1);
+    let t = thread::spawn(move || {
+        let ctx = MockFoo::foo_context();
+        ctx.expect().returning(|x| x + 2);
+        rx.recv().unwrap();
+        assert_eq!(MockFoo::foo(0), 2);
+    });
+    assert_eq!(MockFoo::foo(0), 1);
+    tx.send(()).unwrap();
+    t.join().unwrap();
+    assert_eq!(MockFoo::foo(0), 1);
+}
+
+/// Other threads can't see this thread's expectations
+#[test]
+fn invisible_to_other_threads() {
+    let ctx = MockFoo::foo_context();
+    ctx.expect().return_const(42u32);
+    let r = thread::spawn(|| MockFoo::foo(0)).join();
+    assert!(r.is_err());
+    assert_eq!(MockFoo::foo(0), 42);
+}
+
+#[test]
+fn generic_method() {
+    let ctx = MockFoo::bar_context();
+    ctx.expect::<i16>().return_const(16u32);
+    ctx.expect::<i32>().return_const(32u32);
+    assert_eq!(MockFoo::bar(0i16), 16);
+    assert_eq!(MockFoo::bar(0i32), 32);
+}
+
+#[test]
+fn struct_method() {
+    let ctx = MockBar::bar_context();
+    ctx.expect().return_const(5u32);
+    assert_eq!(MockBar::bar(), 5);
+}
+
+#[test]
+fn module_function() {
+    let ctx = mock_m::baz_context();
+    ctx.expect().returning(|x| x * 2);
+    assert_eq!(mock_m::baz(3), 6);
+}
+
+#[test]
+fn foreign_function() {
+    let ctx = mock_ffi::qux_context();
+    ctx.expect().returning(|x| x * 3);
+    assert_eq!(unsafe { mock_ffi::qux(3) }, 9);
+}
+
+#[test]
+fn associated_type() {
+    let ctx = MockAssoc::assoc_context();
+    ctx.expect().return_const(7u32);
+    assert_eq!(MockAssoc::assoc(), 7);
+}
+
+/// Expectations are still verified when the Context drops
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(<anything>) called 0 time(s) which is fewer than expected 1")]
+fn verified_on_drop() {
+    let ctx = MockFoo::foo_context();
+    ctx.expect()
+        .times(1)
+        .return_const(0u32);
+}
+
+/// A Context verifies the expectations of the thread that created it, even if
+/// it drops on another thread
+#[test]
+fn verified_on_another_thread() {
+    let ctx = MockFoo::foo_context();
+    ctx.expect()
+        .times(1)
+        .return_const(0u32);
+    let r = thread::spawn(move || drop(ctx)).join();
+    let e = r.unwrap_err();
+    assert!(e.downcast_ref::<String>().unwrap()
+        .contains("fewer than expected 1"));
+}
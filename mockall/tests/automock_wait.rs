@@ -0,0 +1,106 @@
+// vim: tw=80
+//! Tests can block until an expectation is satisfied by another thread
+#![deny(warnings)]
+
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::Arc,
+    thread,
+    time::Duration
+};
+
+use mockall::*;
+
+#[automock]
+trait Foo {
+    fn foo(&self, x: u32);
+}
+
+#[test]
+fn already_satisfied() {
+    let mut mock = MockFoo::new();
+    let handle = mock.expect_foo()
+        .times(1)
+        .return_const(())
+        .handle();
+    mock.foo(0);
+    handle.wait(Duration::from_secs(0));
+}
+
+/// An expectation that needn't be called at all is satisfied immediately
+#[test]
+fn optional() {
+    let mut mock = MockFoo::new();
+    let handle = mock.expect_foo()
+        .times(0..)
+        .return_const(())
+        .handle();
+    handle.wait(Duration::from_secs(0));
+}
+
+/// An expectation whose count was never set is satisfied immediately, too
+#[test]
+fn unset_count() {
+    let mut mock = MockFoo::new();
+    let handle = mock.expect_foo()
+        .return_const(())
+        .handle();
+    handle.wait(Duration::from_secs(0));
+}
+
+#[test]
+fn other_thread() {
+    let mut mock = MockFoo::new();
+    let handle = mock.expect_foo()
+        .times(3..)
+        .return_const(())
+        .handle();
+    let mock = Arc::new(mock);
+    let mock2 = mock.clone();
+    let t = thread::spawn(move || {
+        for i in 0..3 {
+            thread::sleep(Duration::from_millis(10));
+            mock2.foo(i);
+        }
+    });
+    handle.wait(Duration::from_secs(60));
+    t.join().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "MockFoo::foo: Expectation(var == 5) named \"five\": not satisfied after waiting 10ms")]
+fn timeout() {
+    let mut mock = MockFoo::new();
+    let handle = mock.expect_foo()
+        .with(predicate::eq(5))
+        .times(1)
+        .named("five")
+        .return_const(())
+        .handle();
+    handle.wait(Duration::from_millis(10));
+}
+
+/// A timeout is reported to the thread's failure handler
+#[test]
+fn timeout_failure_handler() {
+    let failures = Rc::new(RefCell::new(Vec::new()));
+    let failures2 = failures.clone();
+    let _guard = set_failure_handler(move |f: &Failure| {
+        failures2.borrow_mut().push(f.clone());
+    });
+    let mut mock = MockFoo::new();
+    let handle = mock.expect_foo()
+        .times(1)
+        .return_const(())
+        .handle();
+    handle.wait(Duration::from_millis(10));
+    {
+        let failures = failures.borrow();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].kind, FailureKind::TooFewCalls);
+        assert_eq!(failures[0].message,
+            "MockFoo::foo: Expectation(<anything>): not satisfied after waiting 10ms");
+    }
+    mock.foo(0);
+}
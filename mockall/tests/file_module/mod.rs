@@ -0,0 +1,17 @@
+// vim: tw=80
+//! Source of an out-of-line module, mocked by mock_module.rs
+use std::io;
+
+pub fn connect(_addr: &str, _port: u16) -> io::Result<u32> {
+    unimplemented!()
+}
+
+pub fn close(_fd: u32) { unimplemented!() }
+
+pub mod session;
+
+#[path = "nested"]
+pub mod wrapped {
+    #[path = "leaf.rs"]
+    pub mod deep;
+}
@@ -0,0 +1,3 @@
+// vim: tw=80
+//! Source of a `#[path]` submodule of an inline module with its own `#[path]`
+pub fn depth() -> u32 { unimplemented!() }
@@ -0,0 +1,3 @@
+// vim: tw=80
+//! Source of an out-of-line module with a `#[path]` attribute
+pub fn ping() -> bool { unimplemented!() }
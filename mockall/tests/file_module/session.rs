@@ -0,0 +1,7 @@
+// vim: tw=80
+//! Source of an out-of-line submodule of an out-of-line module
+pub fn open(_fd: u32) -> bool { unimplemented!() }
+
+pub mod inline {
+    pub fn id() -> u64 { unimplemented!() }
+}
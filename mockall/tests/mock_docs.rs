@@ -1,4 +1,5 @@
 // vim: tw=80
+//! mock! should allow doc comments in all reasonable positions.
 #![deny(missing_docs)]
 #![deny(warnings)]
 
@@ -8,7 +9,9 @@ use mockall::*;
 // ensures that the code will compile.  mockall_derive has a unit test to ensure
 // that the doc comments are correctly placed.
 
+/// A trait to mock
 pub trait Tr {
+    /// A method
     fn bar(&self);
 }
 
@@ -0,0 +1,70 @@
+// vim: tw=80
+//! Mocking a module whose source is in another file
+#![deny(warnings)]
+
+use mockall::*;
+
+pub mod file_module;
+mock_module!(pub mod file_module;);
+
+#[path = "file_module/renamed.rs"]
+pub mod other;
+mock_module! {
+    #[path = "file_module/renamed.rs"]
+    pub mod other;
+}
+
+// Ensure we can still use the original functions
+pub fn normal_usage() {
+    let _ = file_module::connect("localhost", 80);
+    file_module::close(0);
+    file_module::session::open(0);
+    file_module::wrapped::deep::depth();
+    other::ping();
+}
+
+#[test]
+fn returning() {
+    let ctx = mock_file_module::connect_context();
+    ctx.expect()
+        .withf(|addr, port| addr == "localhost" && *port == 80)
+        .returning(|_, _| Ok(3));
+    assert_eq!(mock_file_module::connect("localhost", 80).unwrap(), 3);
+}
+
+#[test]
+fn unit() {
+    let ctx = mock_file_module::close_context();
+    ctx.expect()
+        .with(predicate::eq(3))
+        .times(1)
+        .return_const(());
+    mock_file_module::close(3);
+}
+
+#[test]
+fn path_attribute() {
+    let ctx = mock_other::ping_context();
+    ctx.expect().return_const(true);
+    assert!(mock_other::ping());
+}
+
+/// Submodules are mocked too, wherever their source is
+#[test]
+fn submodule() {
+    let ctx = mock_file_module::session::open_context();
+    ctx.expect().return_const(true);
+    assert!(mock_file_module::session::open(4));
+    let ctx = mock_file_module::session::inline::id_context();
+    ctx.expect().return_const(7u64);
+    assert_eq!(7, mock_file_module::session::inline::id());
+}
+
+/// Like rustc, look for an inline module's submodules in the directory named
+/// by its own `#[path]`, and resolve their `#[path]`s relative to that.
+#[test]
+fn inline_submodule_path_attribute() {
+    let ctx = mock_file_module::wrapped::deep::depth_context();
+    ctx.expect().return_const(2u32);
+    assert_eq!(2, mock_file_module::wrapped::deep::depth());
+}
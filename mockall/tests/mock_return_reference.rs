@@ -56,14 +56,21 @@ mod sequence {
     use super::*;
 
     #[test]
-    #[should_panic(expected = "exact call count")]
-    fn ambiguous() {
+    fn range() {
         let mut seq = Sequence::new();
         let mut mock = MockFoo::new();
         mock.expect_foo()
             .times(1..3)
+            .return_const(0)
+            .in_sequence(&mut seq);
+        mock.expect_bar()
+            .times(1)
+            .return_const(1)
             .in_sequence(&mut seq);
+
+        mock.foo(4);
         mock.foo(4);
+        mock.bar();
     }
 
     #[test]
@@ -224,15 +224,99 @@ fn returning() {
 mod sequence {
     use super::*;
 
+    /// An expectation with a range of call counts must reach its minimum
+    /// before the sequence can advance
     #[test]
-    #[should_panic(expected = "exact call count")]
-    fn ambiguous() {
+    #[should_panic(expected = "MockFoo::bar(0): Method sequence violation")]
+    fn range_minimum() {
         let mut seq = Sequence::new();
         let mut mock = MockFoo::new();
         mock.expect_baz()
-            .times(1..3)
+            .times(2..)
+            .returning(|| ())
+            .in_sequence(&mut seq);
+        mock.expect_bar()
+            .times(1)
+            .returning(|_| ())
+            .in_sequence(&mut seq);
+
+        mock.baz();
+        mock.bar(0);
+    }
+
+    #[test]
+    fn range_ok() {
+        let mut seq = Sequence::new();
+        let mut mock = MockFoo::new();
+        mock.expect_baz()
+            .times(1..)
+            .returning(|| ())
+            .in_sequence(&mut seq);
+        mock.expect_bar()
+            .times(1)
+            .returning(|_| ())
             .in_sequence(&mut seq);
+
+        mock.baz();
         mock.baz();
+        mock.baz();
+        mock.bar(0);
+    }
+
+    /// An expectation with a range of call counts may not be called again
+    /// once a later step of the sequence has been
+    #[test]
+    #[should_panic(expected = "MockFoo::baz(): Method sequence violation")]
+    fn range_after_later_step() {
+        let mut seq = Sequence::new();
+        let mut mock = MockFoo::new();
+        mock.expect_baz()
+            .times(1..)
+            .returning(|| ())
+            .in_sequence(&mut seq);
+        mock.expect_bar()
+            .times(1)
+            .returning(|_| ())
+            .in_sequence(&mut seq);
+
+        mock.baz();
+        mock.bar(0);
+        mock.baz();
+    }
+
+    /// An expectation that needn't be called may be skipped
+    #[test]
+    fn range_skipped() {
+        let mut seq = Sequence::new();
+        let mut mock = MockFoo::new();
+        mock.expect_baz()
+            .times(..=3)
+            .returning(|| ())
+            .in_sequence(&mut seq);
+        mock.expect_bar()
+            .times(1)
+            .returning(|_| ())
+            .in_sequence(&mut seq);
+
+        mock.bar(0);
+    }
+
+    /// The call count may be set after adding the expectation to the sequence
+    #[test]
+    #[should_panic(expected = "MockFoo::bar(0): Method sequence violation")]
+    fn times_after_in_sequence() {
+        let mut seq = Sequence::new();
+        let mut mock = MockFoo::new();
+        mock.expect_baz()
+            .returning(|| ())
+            .in_sequence(&mut seq)
+            .times(1);
+        mock.expect_bar()
+            .times(1)
+            .returning(|_| ())
+            .in_sequence(&mut seq);
+
+        mock.bar(0);
     }
 
     #[test]
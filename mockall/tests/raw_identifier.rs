@@ -24,6 +24,7 @@ mock! {
     }
 }
 
+#[allow(dead_code)]
 struct r#else {}
 #[automock]
 impl r#while for r#else {
@@ -0,0 +1,136 @@
+// vim: tw=80
+//! The ready-made mocks of std traits
+#![cfg(feature = "std_mocks")]
+#![deny(warnings)]
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    hash::BuildHasher,
+    io::{self, BufRead, Read, Write},
+};
+
+use mockall::{predicate::*, std_mocks::*};
+
+#[test]
+fn read() {
+    let mut mock = MockRead::new();
+    mock.expect_read()
+        .times(1)
+        .returning(|buf| {
+            buf[..3].copy_from_slice(b"abc");
+            Ok(3)
+        });
+    mock.expect_read()
+        .returning(|_| Ok(0));
+    let mut s = String::new();
+    mock.read_to_string(&mut s).unwrap();
+    assert_eq!(s, "abc");
+}
+
+#[test]
+fn write() {
+    let mut mock = MockWrite::new();
+    mock.expect_write()
+        .with(eq(&b"hello"[..]))
+        .returning(|buf| Ok(buf.len()));
+    mock.expect_flush()
+        .times(1)
+        .returning(|| Ok(()));
+    mock.write_all(b"hello").unwrap();
+    mock.flush().unwrap();
+}
+
+#[test]
+fn write_error() {
+    let mut mock = MockWrite::new();
+    mock.expect_write()
+        .returning(|_| Err(io::ErrorKind::BrokenPipe.into()));
+    let e = mock.write_all(b"hello").unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn buf_read() {
+    let mut mock = MockBufRead::new();
+    mock.expect_fill_buf()
+        .times(1)
+        .returning(|| Ok(b"one\n"));
+    mock.expect_consume()
+        .with(eq(4))
+        .return_const(());
+    let mut line = String::new();
+    assert_eq!(4, mock.read_line(&mut line).unwrap());
+    assert_eq!(line, "one\n");
+}
+
+#[test]
+fn iterator() {
+    let mut mock = MockIterator::<u32>::new();
+    let mut seq = mockall::Sequence::new();
+    for i in 1..4 {
+        mock.expect_next()
+            .times(1)
+            .in_sequence(&mut seq)
+            .return_const(Some(i));
+    }
+    mock.expect_next()
+        .return_const(None);
+    assert_eq!(mock.sum::<u32>(), 6);
+}
+
+#[test]
+fn hasher() {
+    struct Builder;
+    impl BuildHasher for Builder {
+        type Hasher = MockHasher;
+        fn build_hasher(&self) -> MockHasher {
+            let mut mock = MockHasher::new();
+            mock.expect_write()
+                .return_const(());
+            mock.expect_finish()
+                .return_const(42u64);
+            mock
+        }
+    }
+    assert_eq!(Builder.hash_one("x"), 42);
+    let mut hm = HashMap::with_hasher(Builder);
+    hm.insert("x", 1);
+    assert_eq!(hm["x"], 1);
+}
+
+#[test]
+fn display() {
+    let mut mock = MockDisplay::new();
+    mock.expect_fmt()
+        .returning(|f| write!(f, "{:>5}", "x"));
+    assert_eq!(mock.to_string(), "    x");
+    assert_eq!(format!("[{mock}]"), "[    x]");
+}
+
+#[test]
+fn from_str() {
+    let ctx = MockFromStr::<String>::from_str_context();
+    ctx.expect()
+        .with(eq("good"))
+        .returning(|_| Ok(MockFromStr::new()));
+    ctx.expect()
+        .returning(|s| Err(format!("bad: {s}")));
+    assert!("good".parse::<MockFromStr<String>>().is_ok());
+    assert_eq!("nope".parse::<MockFromStr<String>>().unwrap_err(),
+               "bad: nope");
+}
+
+#[test]
+fn error() {
+    static CAUSE: std::fmt::Error = std::fmt::Error;
+
+    let mut mock = MockError::new();
+    mock.expect_fmt()
+        .returning(|f| write!(f, "it broke"));
+    mock.expect_source()
+        .returning(|| Some(&CAUSE));
+    let e: Box<dyn Error> = Box::new(mock);
+    assert_eq!(e.to_string(), "it broke");
+    assert!(e.source().unwrap().is::<std::fmt::Error>());
+}
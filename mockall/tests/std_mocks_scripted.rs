@@ -0,0 +1,141 @@
+// vim: tw=80
+//! Scripted reads and writes with the ready-made io mocks
+#![cfg(feature = "std_mocks")]
+#![deny(warnings)]
+
+use std::io::{self, BufReader, BufRead, ErrorKind, Read, Write};
+
+use mockall::std_mocks::*;
+
+#[test]
+fn read_chunks() {
+    let mut mock = MockRead::scripted([
+        ReadStep::Data(b"abc".to_vec()),
+        ReadStep::Data(b"def".to_vec()),
+    ]);
+    let mut buf = [0u8; 16];
+    assert_eq!(mock.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], b"abc");
+    assert_eq!(mock.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], b"def");
+    assert_eq!(mock.read(&mut buf).unwrap(), 0);
+    assert_eq!(mock.read(&mut buf).unwrap(), 0);
+}
+
+/// A chunk larger than the caller's buffer is split across several reads
+#[test]
+fn read_small_buffer() {
+    let mut mock = MockRead::scripted([ReadStep::Data(b"abcde".to_vec())]);
+    let mut buf = [0u8; 2];
+    assert_eq!(mock.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf, b"ab");
+    assert_eq!(mock.read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf, b"cd");
+    assert_eq!(mock.read(&mut buf).unwrap(), 1);
+    assert_eq!(&buf[..1], b"e");
+    assert_eq!(mock.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn read_errors() {
+    let mut mock = MockRead::scripted([
+        ReadStep::Error(ErrorKind::WouldBlock),
+        ReadStep::Eof,
+        ReadStep::Data(b"x".to_vec()),
+    ]);
+    let mut buf = [0u8; 4];
+    let e = mock.read(&mut buf).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::WouldBlock);
+    assert_eq!(mock.read(&mut buf).unwrap(), 0);
+    assert_eq!(mock.read(&mut buf).unwrap(), 1);
+}
+
+/// Code that handles short reads and retries `Interrupted` should see the
+/// whole stream
+#[test]
+fn read_lines() {
+    let mock = MockRead::scripted([
+        ReadStep::Data(b"one\ntw".to_vec()),
+        ReadStep::Error(ErrorKind::Interrupted),
+        ReadStep::Data(b"o\nthree".to_vec()),
+    ]);
+    let lines = BufReader::new(mock).lines()
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(lines, ["one", "two", "three"]);
+}
+
+#[test]
+fn recording() {
+    let (mut mock, log) = MockWrite::recording();
+    write!(mock, "{}-{}", 1, 2).unwrap();
+    mock.flush().unwrap();
+    assert_eq!(log.contents(), b"1-2");
+}
+
+#[test]
+fn write_short() {
+    let (mut mock, log) = MockWrite::scripted([
+        WriteStep::Accept(2),
+        WriteStep::Error(ErrorKind::Interrupted),
+        WriteStep::Accept(1),
+    ]);
+    mock.write_all(b"hello").unwrap();
+    assert_eq!(log.contents(), b"hello");
+}
+
+#[test]
+fn write_error() {
+    let (mut mock, log) = MockWrite::scripted([
+        WriteStep::Accept(2),
+        WriteStep::Error(ErrorKind::BrokenPipe),
+    ]);
+    let e = mock.write_all(b"hello").unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::BrokenPipe);
+    assert_eq!(log.contents(), b"he");
+}
+
+/// The log may be inspected after the mock has been moved away
+#[test]
+fn write_moved() {
+    let (mock, log) = MockWrite::recording();
+    let mut w = io::BufWriter::new(mock);
+    w.write_all(b"buffered").unwrap();
+    assert!(log.contents().is_empty());
+    drop(w);
+    assert_eq!(log.contents(), b"buffered");
+}
+
+#[cfg(feature = "futures-io")]
+mod futures_io {
+    use super::*;
+    use futures::{
+        executor::block_on,
+        io::{AsyncReadExt, AsyncWriteExt}
+    };
+
+    #[test]
+    fn read() {
+        let mut mock = MockAsyncRead::scripted([
+            ReadStep::Data(b"abc".to_vec()),
+            ReadStep::Error(ErrorKind::WouldBlock),
+            ReadStep::Data(b"def".to_vec()),
+        ]);
+        let mut s = String::new();
+        block_on(mock.read_to_string(&mut s)).unwrap();
+        assert_eq!(s, "abcdef");
+    }
+
+    #[test]
+    fn write() {
+        let (mut mock, log) = MockAsyncWrite::scripted([
+            WriteStep::Accept(1),
+            WriteStep::Error(ErrorKind::WouldBlock),
+        ]);
+        block_on(async {
+            mock.write_all(b"hello").await.unwrap();
+            mock.close().await.unwrap();
+        });
+        assert_eq!(log.contents(), b"hello");
+    }
+}
@@ -7,6 +7,8 @@ use syn::parse::{Parse, ParseStream};
 // This enum is very short-lived, so it's fine not to box it.
 #[allow(clippy::large_enum_variant)]
 enum Attr {
+    /// Store static methods' expectations per thread instead of globally
+    StaticExpectations(bool),
     Type(TraitItemType),
 }
 
@@ -15,6 +17,27 @@ impl Parse for Attr {
         let lookahead = input.lookahead1();
         if lookahead.peek(Token![type]) {
             input.parse().map(Attr::Type)
+        } else if lookahead.peek(Ident) {
+            let ident: Ident = input.parse()?;
+            if ident != "static_expectations" {
+                return Err(Error::new(ident.span(),
+                    "unknown automock attribute"));
+            }
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+            let thread_local = match value.value().as_str() {
+                "global" => false,
+                "thread_local" => true,
+                _ => return Err(Error::new(value.span(),
+                    "static_expectations must be \"global\" or \"thread_local\""))
+            };
+            // Allow either separator before any following attribute
+            if input.peek(Token![;]) {
+                input.parse::<Token![;]>()?;
+            } else if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+            Ok(Attr::StaticExpectations(thread_local))
         } else {
             Err(lookahead.error())
         }
@@ -25,6 +48,8 @@ impl Parse for Attr {
 #[derive(Debug, Default)]
 pub(crate) struct Attrs {
     pub attrs: HashMap<Ident, Type>,
+    /// Are static methods' expectations stored per thread?
+    pub thread_local: bool,
 }
 
 impl Attrs {
@@ -41,32 +66,35 @@ impl Attrs {
         }
     }
 
-    pub(crate) fn substitute_item_impl(&self, item_impl: &mut ItemImpl) {
+    pub(crate) fn substitute_item_impl(&self, item_impl: &mut ItemImpl)
+        -> Result<()>
+    {
         let (_, trait_path, _) = item_impl.trait_.as_ref()
-            .expect("Should only be called for trait item impls");
+            .ok_or_else(|| Error::new(item_impl.span(),
+                "automock can only substitute types in trait impls"))?;
         let trait_ident = find_ident_from_path(trait_path).0;
+        let mut errors = Errors::default();
         for item in item_impl.items.iter_mut() {
             if let ImplItem::Fn(method) = item {
-                let sig = &mut method.sig;
-                for fn_arg in sig.inputs.iter_mut() {
-                    if let FnArg::Typed(arg) = fn_arg {
-                        self.substitute_type(&mut arg.ty, &trait_ident);
-                    }
-                }
-                if let ReturnType::Type(_, ref mut ty) = &mut sig.output {
-                    self.substitute_type(ty, &trait_ident);
-                }
+                errors.ok(self.substitute_sig(&mut method.sig, &trait_ident));
             }
         }
+        errors.finish(())
     }
 
     fn substitute_path_segment(&self, seg: &mut PathSegment, traitname: &Ident)
+        -> Result<()>
     {
         match &mut seg.arguments {
             PathArguments::None => /* nothing to do */(),
             PathArguments::Parenthesized(p) => {
-                compile_error(p.span(),
-                    "Mockall does not support mocking Fn objects.  See https://github.com/asomers/mockall/issues/139");
+                // Fn objects, like `Fn(Self::T) -> Self::U`
+                for input in p.inputs.iter_mut() {
+                    self.substitute_type(input, traitname)?;
+                }
+                if let ReturnType::Type(_, ref mut ty) = &mut p.output {
+                    self.substitute_type(ty, traitname)?;
+                }
             },
             PathArguments::AngleBracketed(abga) => {
                 for arg in abga.args.iter_mut() {
@@ -78,13 +106,13 @@ impl Attrs {
                              */
                         }
                         GenericArgument::Type(ty) => {
-                            self.substitute_type(ty, traitname)
+                            self.substitute_type(ty, traitname)?
                         },
                         GenericArgument::AssocConst(_) => {
                             // Nothing to do
                         }
                         GenericArgument::AssocType(at) => {
-                            self.substitute_type(&mut at.ty, traitname);
+                            self.substitute_type(&mut at.ty, traitname)?;
                         }
                         // TODO: Constraints
                         _ => {
@@ -94,34 +122,51 @@ impl Attrs {
                 }
             },
         }
+        Ok(())
+    }
+
+    /// Substitute types in a method's arguments and return type
+    fn substitute_sig(&self, sig: &mut Signature, traitname: &Ident)
+        -> Result<()>
+    {
+        let mut errors = Errors::default();
+        for fn_arg in sig.inputs.iter_mut() {
+            if let FnArg::Typed(arg) = fn_arg {
+                errors.ok(self.substitute_type(&mut arg.ty, traitname));
+            }
+        }
+        if let ReturnType::Type(_, ref mut ty) = &mut sig.output {
+            errors.ok(self.substitute_type(ty, traitname));
+        }
+        errors.finish(())
     }
 
     /// Recursively substitute types in the input
-    fn substitute_type(&self, ty: &mut Type, traitname: &Ident) {
+    fn substitute_type(&self, ty: &mut Type, traitname: &Ident) -> Result<()> {
         match ty {
             Type::Slice(s) => {
-                self.substitute_type(s.elem.as_mut(), traitname)
+                self.substitute_type(s.elem.as_mut(), traitname)?
             },
             Type::Array(a) => {
-                self.substitute_type(a.elem.as_mut(), traitname)
+                self.substitute_type(a.elem.as_mut(), traitname)?
             },
             Type::Ptr(p) => {
-                self.substitute_type(p.elem.as_mut(), traitname)
+                self.substitute_type(p.elem.as_mut(), traitname)?
             },
             Type::Reference(r) => {
-                self.substitute_type(r.elem.as_mut(), traitname)
+                self.substitute_type(r.elem.as_mut(), traitname)?
             },
             Type::BareFn(bfn) => {
                 for fn_arg in bfn.inputs.iter_mut() {
-                    self.substitute_type(&mut fn_arg.ty, traitname);
+                    self.substitute_type(&mut fn_arg.ty, traitname)?;
                 }
                 if let ReturnType::Type(_, ref mut ty) = &mut bfn.output {
-                    self.substitute_type(ty, traitname);
+                    self.substitute_type(ty, traitname)?;
                 }
             },
             Type::Tuple(tuple) => {
                 for elem in tuple.elems.iter_mut() {
-                    self.substitute_type(elem, traitname)
+                    self.substitute_type(elem, traitname)?
                 }
             }
             Type::Path(path) => {
@@ -129,15 +174,16 @@ impl Attrs {
                     let qp = if let Type::Path(p) = qself.ty.as_ref() {
                         &p.path
                     } else {
-                        panic!("QSelf's type isn't a path?")
+                        return Err(Error::new(qself.ty.span(),
+                            "mockall does not support qualified self types (`<T as Trait>::X`) here"));
                     };
                     let qident = &qp.segments.first().unwrap().ident;
                     if qself.position != 1
                         || qp.segments.len() != 1
                         || path.path.segments.len() != 2
                         || qident != "Self" {
-                        compile_error(path.span(),
-                            "QSelf is a work in progress");
+                        return Err(Error::new(path.span(),
+                            "mockall does not support qualified self types (`<T as Trait>::X`) here"));
                     }
 
                     let mut seg_iter = path.path.segments.iter().rev();
@@ -148,73 +194,78 @@ impl Attrs {
                     drop(seg_iter);
 
                     if qident != traitname {
-                        compile_error(qident.span(),
-                            "Mockall does not support QSelf substitutions except for the trait being mocked");
+                        return Err(Error::new(qident.span(),
+                            "Mockall does not support QSelf substitutions except for the trait being mocked"));
                     }
                     if let Some(new_type) = self.attrs.get(to_sub) {
                         *ty = new_type.clone();
                     } else {
-                        compile_error(to_sub.span(),
-                            "Unknown type substitution for QSelf");
+                        return Err(Error::new(to_sub.span(),
+                            "Unknown type substitution for QSelf"));
                     }
                 } else if let Some(newty) = self.get_path(&path.path) {
                     *ty = newty;
                 } else {
                     for seg in path.path.segments.iter_mut() {
-                        self.substitute_path_segment(seg, traitname);
+                        self.substitute_path_segment(seg, traitname)?;
                     }
                 }
             },
             Type::TraitObject(to) => {
                 for bound in to.bounds.iter_mut() {
-                    self.substitute_type_param_bound(bound, traitname);
+                    self.substitute_type_param_bound(bound, traitname)?;
                 }
             },
             Type::ImplTrait(it) => {
                 for bound in it.bounds.iter_mut() {
-                    self.substitute_type_param_bound(bound, traitname);
+                    self.substitute_type_param_bound(bound, traitname)?;
                 }
             },
             Type::Paren(p) => {
-                self.substitute_type(p.elem.as_mut(), traitname)
+                self.substitute_type(p.elem.as_mut(), traitname)?
             },
             Type::Group(g) => {
-                self.substitute_type(g.elem.as_mut(), traitname)
+                self.substitute_type(g.elem.as_mut(), traitname)?
             },
             Type::Macro(_) | Type::Verbatim(_) => {
-                compile_error(ty.span(),
-                    "mockall_derive does not support this type when using associated types");
+                return Err(Error::new(ty.span(),
+                    "mockall_derive does not support this type when using associated types"));
             },
             Type::Infer(_) | Type::Never(_) => {
                 /* Nothing to do */
             },
-            _ => compile_error(ty.span(), "Unsupported type"),
+            _ => return Err(Error::new(ty.span(), "Unsupported type")),
         }
+        Ok(())
     }
 
     fn substitute_type_param_bound(&self,
                                    bound: &mut TypeParamBound,
-                                   traitname: &Ident)
+                                   traitname: &Ident) -> Result<()>
     {
         if let TypeParamBound::Trait(t) = bound {
             match self.get_path(&t.path) {
                 None => {
                     for seg in t.path.segments.iter_mut() {
-                        self.substitute_path_segment(seg, traitname);
+                        self.substitute_path_segment(seg, traitname)?;
                     }
                 },
                 Some(Type::Path(type_path)) => {
                     t.path = type_path.path;
                 },
                 Some(_) => {
-                    compile_error(t.path.span(),
-                        "Can only substitute paths for trait bounds");
+                    return Err(Error::new(t.path.span(),
+                        "Can only substitute paths for trait bounds"));
                 }
             }
         }
+        Ok(())
     }
 
-    pub(crate) fn substitute_trait(&self, item: &ItemTrait) -> ItemTrait {
+    pub(crate) fn substitute_trait(&self, item: &ItemTrait)
+        -> Result<ItemTrait>
+    {
+        let mut errors = Errors::default();
         let mut output = item.clone();
         for trait_item in output.items.iter_mut() {
             match trait_item {
@@ -226,48 +277,46 @@ impl Attrs {
                         // bounds
                         tity.bounds = Punctuated::new();
                     } else {
-                        compile_error(tity.span(),
-                            "Default value not given for associated type");
+                        errors.push(Error::new(tity.span(),
+                            "Default value not given for associated type"));
                     }
                 },
                 TraitItem::Fn(method) => {
-                    let sig = &mut method.sig;
-                    for fn_arg in sig.inputs.iter_mut() {
-                        if let FnArg::Typed(arg) = fn_arg {
-                            self.substitute_type(&mut arg.ty, &item.ident);
-                        }
-                    }
-                    if let ReturnType::Type(_, ref mut ty) = &mut sig.output {
-                        self.substitute_type(ty, &item.ident);
-                    }
+                    let r = self.substitute_sig(&mut method.sig, &item.ident);
+                    errors.ok(r);
                 },
                 _ => {
                     // Nothing to do
                 }
             }
         }
-        output
+        errors.finish(output)
     }
 }
 
 impl Parse for Attrs {
     fn parse(input: ParseStream) -> parse::Result<Self> {
         let mut attrs = HashMap::new();
+        let mut errors = Errors::default();
+        let mut thread_local = false;
         while !input.is_empty() {
             let attr: Attr = input.parse()?;
             match attr {
+                Attr::StaticExpectations(tl) => {
+                    thread_local = tl;
+                },
                 Attr::Type(trait_item_type) => {
                     let ident = trait_item_type.ident.clone();
                     if let Some((_, ty)) = trait_item_type.default {
                         attrs.insert(ident, ty.clone());
                     } else {
-                        compile_error(trait_item_type.span(),
-                          "automock type attributes must have a default value");
+                        errors.push(Error::new(trait_item_type.span(),
+                          "automock type attributes must have a default value"));
                     }
                 }
             }
         }
-        Ok(Attrs{attrs})
+        errors.finish(Attrs{attrs, thread_local})
     }
 }
 
@@ -286,10 +335,22 @@ mod t {
         let _self: super::Attrs = parse2(attrs).unwrap();
         let mut in_ty: Type = parse2(input).unwrap();
         let expect_ty: Type = parse2(expected).unwrap();
-        _self.substitute_type(&mut in_ty, &traitname);
+        _self.substitute_type(&mut in_ty, &traitname).unwrap();
         assert_eq!(in_ty, expect_ty);
     }
 
+    fn check_substitute_type_error(
+        attrs: TokenStream,
+        input: TokenStream,
+        traitname: Ident,
+        expected: &str)
+    {
+        let _self: super::Attrs = parse2(attrs).unwrap();
+        let mut in_ty: Type = parse2(input).unwrap();
+        let e = _self.substitute_type(&mut in_ty, &traitname).unwrap_err();
+        assert_eq!(e.to_string(), expected);
+    }
+
     #[test]
     fn qself() {
         check_substitute_type(quote!(type T = u32;),
@@ -299,20 +360,57 @@ mod t {
     }
 
     #[test]
-    #[should_panic(expected = "Mockall does not support QSelf substitutions except for the trait being mocked")]
     fn qself_other() {
-        check_substitute_type(quote!(type T = u32;),
-                              quote!(<Self as AsRef>::T),
-                              format_ident!("Foo"),
-                              quote!(u32));
+        check_substitute_type_error(quote!(type T = u32;),
+            quote!(<Self as AsRef>::T),
+            format_ident!("Foo"),
+            "Mockall does not support QSelf substitutions except for the trait being mocked");
+    }
+
+    #[test]
+    fn qself_not_path() {
+        check_substitute_type_error(quote!(type T = u32;),
+            quote!(<[u8] as Foo>::T),
+            format_ident!("Foo"),
+            "mockall does not support qualified self types (`<T as Trait>::X`) here");
+    }
+
+    #[test]
+    fn static_expectations_global() {
+        let attrs: super::Attrs =
+            parse2(quote!(static_expectations = "global")).unwrap();
+        assert!(!attrs.thread_local);
+    }
+
+    #[test]
+    fn static_expectations_invalid() {
+        let e = parse2::<super::Attrs>(
+            quote!(static_expectations = "per_process")
+        ).err().unwrap();
+        assert_eq!(e.to_string(),
+            "static_expectations must be \"global\" or \"thread_local\"");
+    }
+
+    #[test]
+    fn static_expectations_thread_local() {
+        let attrs: super::Attrs = parse2(quote!(
+            static_expectations = "thread_local", type T = u32;
+        )).unwrap();
+        assert!(attrs.thread_local);
+        assert_eq!(attrs.attrs.len(), 1);
+    }
+
+    #[test]
+    fn unknown_attr() {
+        let e = parse2::<super::Attrs>(quote!(foo = "bar")).err().unwrap();
+        assert_eq!(e.to_string(), "unknown automock attribute");
     }
 
     #[test]
-    #[should_panic(expected = "Unknown type substitution for QSelf")]
     fn unknown_substitution() {
-        check_substitute_type(quote!(type T = u32;),
-                              quote!(<Self as Foo>::Q),
-                              format_ident!("Foo"),
-                              quote!(u32));
+        check_substitute_type_error(quote!(type T = u32;),
+            quote!(<Self as Foo>::Q),
+            format_ident!("Foo"),
+            "Unknown type substitution for QSelf");
     }
 }
@@ -5,7 +5,6 @@
 //! its reexports via the [`mockall`](https://docs.rs/mockall/latest/mockall)
 //! crate.
 
-#![cfg_attr(feature = "nightly_derive", feature(proc_macro_diagnostic))]
 #![cfg_attr(test, deny(warnings))]
 
 use cfg_if::cfg_if;
@@ -38,19 +37,29 @@ use crate::mockable_item::MockableItem;
 type HashMap<K, V> = std::collections::HashMap<K, V, BuildHasherDefault<std::collections::hash_map::DefaultHasher>>;
 type HashSet<K> = std::collections::HashSet<K, BuildHasherDefault<std::collections::hash_map::DefaultHasher>>;
 
-cfg_if! {
-    // proc-macro2's Span::unstable method requires the nightly feature, and it
-    // doesn't work in test mode.
-    // https://github.com/alexcrichton/proc-macro2/issues/159
-    if #[cfg(all(feature = "nightly_derive", not(test)))] {
-        fn compile_error(span: Span, msg: &str) {
-            span.unstable()
-                .error(msg)
-                .emit();
+/// Collects errors, so that several of them can be reported at once.
+#[derive(Default)]
+struct Errors(Option<Error>);
+
+impl Errors {
+    /// Record an error.
+    fn push(&mut self, e: Error) {
+        match &mut self.0 {
+            Some(errs) => errs.combine(e),
+            None => self.0 = Some(e)
         }
-    } else {
-        fn compile_error(_span: Span, msg: &str) {
-            panic!("{msg}.  More information may be available when mockall is built with the \"nightly\" feature.");
+    }
+
+    /// Record `r`'s error, if it has one.  Otherwise return its value.
+    fn ok<T>(&mut self, r: Result<T>) -> Option<T> {
+        r.map_err(|e| self.push(e)).ok()
+    }
+
+    /// Return `t`, or else every error that was recorded.
+    fn finish<T>(self, t: T) -> Result<T> {
+        match self.0 {
+            Some(e) => Err(e),
+            None => Ok(t)
         }
     }
 }
@@ -74,8 +83,9 @@ fn is_concretize(attr: &Attribute) -> bool {
 
 /// replace generic arguments with concrete trait object arguments
 fn concretize_args(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
-    (Generics, Vec<FnArg>, Vec<TokenStream>)
+    Result<(Generics, Vec<FnArg>, Vec<TokenStream>)>
 {
+    let mut errors = Errors::default();
     let mut hm = HashMap::default();
 
     let mut save_types = |ident: &Ident, tpb: &Punctuated<TypeParamBound, Token![+]>| {
@@ -89,8 +99,8 @@ fn concretize_args(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
                 let subst_ty: Type = parse2(quote!(&#ident)).unwrap();
                 hm.insert(subst_ty, (newty, None));
             } else {
-                compile_error(tpb.span(),
-                    "Type cannot be made into a trait object");
+                errors.push(Error::new(tpb.span(),
+                    "Type cannot be made into a trait object"));
             }
 
             if let Ok(newty) = parse2::<Type>(quote!(&mut (dyn #tpb))) {
@@ -98,8 +108,8 @@ fn concretize_args(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
                 let subst_ty: Type = parse2(quote!(&mut #ident)).unwrap();
                 hm.insert(subst_ty, (newty, None));
             } else {
-                compile_error(tpb.span(),
-                    "Type cannot be made into a trait object");
+                errors.push(Error::new(tpb.span(),
+                    "Type cannot be made into a trait object"));
             }
 
             // I wish we could substitute &[T] arguments.  But there's no way
@@ -108,8 +118,8 @@ fn concretize_args(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
                 let subst_ty: Type = parse2(quote!(&[#ident])).unwrap();
                 hm.insert(subst_ty, (newty, Some(tpb.clone())));
             } else {
-                compile_error(tpb.span(),
-                    "Type cannot be made into a trait object");
+                errors.push(Error::new(tpb.span(),
+                    "Type cannot be made into a trait object"));
             }
         }
     };
@@ -142,7 +152,7 @@ fn concretize_args(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
     let outargs: Vec<FnArg> = args.iter().map(|arg| {
         if let FnArg::Typed(pt) = arg {
             let mut immutable_pt = pt.clone();
-            demutify_arg(&mut immutable_pt);
+            errors.ok(demutify_arg(&mut immutable_pt));
             if let Some((newty, _)) = hm.get(&pt.ty) {
                 FnArg::Typed(PatType {
                     attrs: Vec::default(),
@@ -169,7 +179,8 @@ fn concretize_args(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
         match arg {
             FnArg::Typed(pt) => {
                 let mut pt2 = pt.clone();
-                demutify_arg(&mut pt2);
+                // Any error was already recorded for outargs
+                let _ = demutify_arg(&mut pt2);
                 let pat = &pt2.pat;
                 if pat_is_self(pat) {
                     None
@@ -196,7 +207,7 @@ fn concretize_args(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
             FnArg::Receiver(_) => None,
         }
     }).collect();
-    (outg, outargs, call_exprs)
+    errors.finish((outg, outargs, call_exprs))
 }
 
 fn deanonymize_lifetime(lt: &mut Lifetime) {
@@ -205,7 +216,7 @@ fn deanonymize_lifetime(lt: &mut Lifetime) {
     }
 }
 
-fn deanonymize_path(path: &mut Path) {
+fn deanonymize_path(path: &mut Path) -> Result<()> {
     for seg in path.segments.iter_mut() {
         match &mut seg.arguments {
             PathArguments::None => (),
@@ -216,46 +227,54 @@ fn deanonymize_path(path: &mut Path) {
                     }
                 }
             },
-            _ => compile_error(seg.arguments.span(),
-                "Methods returning functions are TODO"),
+            PathArguments::Parenthesized(pga) => {
+                for input in pga.inputs.iter_mut() {
+                    deanonymize(input)?;
+                }
+                if let ReturnType::Type(_, ref mut bt) = pga.output {
+                    deanonymize(bt.as_mut())?;
+                }
+            },
         }
     }
+    Ok(())
 }
 
 /// Replace any references to the anonymous lifetime `'_` with `'static`.
-fn deanonymize(literal_type: &mut Type) {
+fn deanonymize(literal_type: &mut Type) -> Result<()> {
     match literal_type {
-        Type::Array(ta) => deanonymize(ta.elem.as_mut()),
+        Type::Array(ta) => deanonymize(ta.elem.as_mut())?,
         Type::BareFn(tbf) => {
             if let ReturnType::Type(_, ref mut bt) = tbf.output {
-                deanonymize(bt.as_mut());
+                deanonymize(bt.as_mut())?;
             }
             for input in tbf.inputs.iter_mut() {
-                deanonymize(&mut input.ty);
+                deanonymize(&mut input.ty)?;
             }
         },
-        Type::Group(tg) => deanonymize(tg.elem.as_mut()),
+        Type::Group(tg) => deanonymize(tg.elem.as_mut())?,
         Type::Infer(_) => (),
         Type::Never(_) => (),
-        Type::Paren(tp) => deanonymize(tp.elem.as_mut()),
+        Type::Paren(tp) => deanonymize(tp.elem.as_mut())?,
         Type::Path(tp) => {
             if let Some(ref mut qself) = tp.qself {
-                deanonymize(qself.ty.as_mut());
+                deanonymize(qself.ty.as_mut())?;
             }
-            deanonymize_path(&mut tp.path);
+            deanonymize_path(&mut tp.path)?;
         },
-        Type::Ptr(tptr) => deanonymize(tptr.elem.as_mut()),
+        Type::Ptr(tptr) => deanonymize(tptr.elem.as_mut())?,
         Type::Reference(tr) => {
             if let Some(lt) = tr.lifetime.as_mut() {
                 deanonymize_lifetime(lt)
             }
-            deanonymize(tr.elem.as_mut());
+            deanonymize(tr.elem.as_mut())?;
         },
-        Type::Slice(s) => deanonymize(s.elem.as_mut()),
+        Type::Slice(s) => deanonymize(s.elem.as_mut())?,
         Type::TraitObject(tto) => {
             for tpb in tto.bounds.iter_mut() {
                 match tpb {
-                    TypeParamBound::Trait(tb) => deanonymize_path(&mut tb.path),
+                    TypeParamBound::Trait(tb) =>
+                        deanonymize_path(&mut tb.path)?,
                     TypeParamBound::Lifetime(lt) => deanonymize_lifetime(lt),
                     _ => ()
                 }
@@ -263,18 +282,21 @@ fn deanonymize(literal_type: &mut Type) {
         },
         Type::Tuple(tt) => {
             for ty in tt.elems.iter_mut() {
-                deanonymize(ty)
+                deanonymize(ty)?
             }
         }
-        x => compile_error(x.span(), "Unimplemented type for deanonymize")
+        x => return Err(Error::new(x.span(),
+            "Unimplemented type for deanonymize"))
     }
+    Ok(())
 }
 
 // If there are any closures in the argument list, turn them into boxed
 // functions
 fn declosurefy(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
-    (Generics, Vec<FnArg>, Vec<TokenStream>)
+    Result<(Generics, Vec<FnArg>, Vec<TokenStream>)>
 {
+    let mut errors = Errors::default();
     let mut hm = HashMap::default();
 
     let mut save_fn_types = |ident: &Ident, tpb: &TypeParamBound| {
@@ -283,8 +305,10 @@ fn declosurefy(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
             if ["Fn", "FnMut", "FnOnce"].iter().any(|s| fident == *s) {
                 let newty: Type = parse2(quote!(Box<dyn #tb>)).unwrap();
                 let subst_ty: Type = parse2(quote!(#ident)).unwrap();
-                assert!(hm.insert(subst_ty, newty).is_none(),
-                    "A generic parameter had two Fn bounds?");
+                if hm.insert(subst_ty, newty).is_some() {
+                    errors.push(Error::new(tpb.span(),
+                        "A generic parameter may have only one Fn bound"));
+                }
             }
         }
     };
@@ -358,7 +382,7 @@ fn declosurefy(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
     let outargs = args.iter().map(|arg| {
         if let FnArg::Typed(pt) = arg {
             let mut immutable_pt = pt.clone();
-            demutify_arg(&mut immutable_pt);
+            errors.ok(demutify_arg(&mut immutable_pt));
             if let Some(newty) = hm.get(&pt.ty) {
                 FnArg::Typed(PatType {
                     attrs: Vec::default(),
@@ -385,7 +409,8 @@ fn declosurefy(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
         match arg {
             FnArg::Typed(pt) => {
                 let mut pt2 = pt.clone();
-                demutify_arg(&mut pt2);
+                // Any error was already recorded for outargs
+                let _ = demutify_arg(&mut pt2);
                 let pat = &pt2.pat;
                 if pat_is_self(pat) {
                     None
@@ -398,7 +423,7 @@ fn declosurefy(gen: &Generics, args: &Punctuated<FnArg, Token![,]>) ->
             FnArg::Receiver(_) => None,
         }
     }).collect();
-    (outg, outargs, callargs)
+    errors.finish((outg, outargs, callargs))
 }
 
 /// Replace any "impl trait" types with "Box<dyn trait>" or equivalent.
@@ -452,42 +477,47 @@ fn dewhereselfify(generics: &mut Generics) {
 }
 
 /// Remove any mutability qualifiers from a method's argument list
-fn demutify(inputs: &mut Punctuated<FnArg, token::Comma>) {
+fn demutify(inputs: &mut Punctuated<FnArg, token::Comma>) -> Result<()> {
+    let mut errors = Errors::default();
     for arg in inputs.iter_mut() {
         match arg {
             FnArg::Receiver(r) => if r.reference.is_none() {
                 r.mutability = None
             },
-            FnArg::Typed(pt) => demutify_arg(pt),
+            FnArg::Typed(pt) => {
+                errors.ok(demutify_arg(pt));
+            }
         }
     }
+    errors.finish(())
 }
 
 /// Remove any "mut" from a method argument's binding.
-fn demutify_arg(arg: &mut PatType) {
+fn demutify_arg(arg: &mut PatType) -> Result<()> {
     match *arg.pat {
         Pat::Wild(_) => {
-            compile_error(arg.span(),
-                "Mocked methods must have named arguments");
+            Err(Error::new(arg.span(),
+                "Mocked methods must have named arguments"))
         },
         Pat::Ident(ref mut pat_ident) => {
             if let Some(r) = &pat_ident.by_ref {
-                compile_error(r.span(),
-                    "Mockall does not support by-reference argument bindings");
+                return Err(Error::new(r.span(),
+                    "Mockall does not support by-reference argument bindings"));
             }
             if let Some((_at, subpat)) = &pat_ident.subpat {
-                compile_error(subpat.span(),
-                    "Mockall does not support subpattern bindings");
+                return Err(Error::new(subpat.span(),
+                    "Mockall does not support subpattern bindings"));
             }
             pat_ident.mutability = None;
+            Ok(())
         },
-        _ => {
-            compile_error(arg.span(), "Unsupported argument type");
-        }
-    };
+        _ => Err(Error::new(arg.span(), "Unsupported argument type"))
+    }
 }
 
-fn deselfify_path(path: &mut Path, actual: &Ident, generics: &Generics) {
+fn deselfify_path(path: &mut Path, actual: &Ident, generics: &Generics)
+    -> Result<()>
+{
     for seg in path.segments.iter_mut() {
         if seg.ident == "Self" {
             seg.ident = actual.clone();
@@ -512,7 +542,15 @@ fn deselfify_path(path: &mut Path, actual: &Ident, generics: &Generics) {
                                         ld.lifetime.clone()
                                     )
                                 }
-                                _ => unimplemented!(),
+                                GenericParam::Const(cp) => {
+                                    GenericArgument::Const(
+                                        Expr::Path(ExprPath {
+                                            attrs: Vec::new(),
+                                            qself: None,
+                                            path: Path::from(cp.ident.clone())
+                                        })
+                                    )
+                                }
                             }
                         }).collect::<Punctuated<_, _>>();
                     seg.arguments = PathArguments::AngleBracketed(
@@ -525,8 +563,8 @@ fn deselfify_path(path: &mut Path, actual: &Ident, generics: &Generics) {
                     );
                 }
             } else {
-                compile_error(seg.arguments.span(),
-                    "Type arguments after Self are unexpected");
+                return Err(Error::new(seg.arguments.span(),
+                    "Type arguments after Self are unexpected"));
             }
         }
         if let PathArguments::AngleBracketed(abga) = &mut seg.arguments
@@ -534,59 +572,62 @@ fn deselfify_path(path: &mut Path, actual: &Ident, generics: &Generics) {
             for arg in abga.args.iter_mut() {
                 match arg {
                     GenericArgument::Type(ty) =>
-                        deselfify(ty, actual, generics),
+                        deselfify(ty, actual, generics)?,
                     GenericArgument::AssocType(at) =>
-                        deselfify(&mut at.ty, actual, generics),
+                        deselfify(&mut at.ty, actual, generics)?,
                     _ => /* Nothing to do */(),
                 }
             }
         }
     }
+    Ok(())
 }
 
 /// Replace any references to `Self` in `literal_type` with `actual`.
 /// `generics` is the Generics field of the parent struct.  Useful for
 /// constructor methods.
-fn deselfify(literal_type: &mut Type, actual: &Ident, generics: &Generics) {
+fn deselfify(literal_type: &mut Type, actual: &Ident, generics: &Generics)
+    -> Result<()>
+{
     match literal_type {
         Type::Slice(s) => {
-            deselfify(s.elem.as_mut(), actual, generics);
+            deselfify(s.elem.as_mut(), actual, generics)?;
         },
         Type::Array(a) => {
-            deselfify(a.elem.as_mut(), actual, generics);
+            deselfify(a.elem.as_mut(), actual, generics)?;
         },
         Type::Ptr(p) => {
-            deselfify(p.elem.as_mut(), actual, generics);
+            deselfify(p.elem.as_mut(), actual, generics)?;
         },
         Type::Reference(r) => {
-            deselfify(r.elem.as_mut(), actual, generics);
+            deselfify(r.elem.as_mut(), actual, generics)?;
         },
         Type::Tuple(tuple) => {
             for elem in tuple.elems.iter_mut() {
-                deselfify(elem, actual, generics);
+                deselfify(elem, actual, generics)?;
             }
         }
         Type::Path(type_path) => {
             if let Some(ref mut qself) = type_path.qself {
-                deselfify(qself.ty.as_mut(), actual, generics);
+                deselfify(qself.ty.as_mut(), actual, generics)?;
             }
-            deselfify_path(&mut type_path.path, actual, generics);
+            deselfify_path(&mut type_path.path, actual, generics)?;
         },
         Type::Paren(p) => {
-            deselfify(p.elem.as_mut(), actual, generics);
+            deselfify(p.elem.as_mut(), actual, generics)?;
         },
         Type::Group(g) => {
-            deselfify(g.elem.as_mut(), actual, generics);
+            deselfify(g.elem.as_mut(), actual, generics)?;
         },
         Type::Macro(_) | Type::Verbatim(_) => {
-            compile_error(literal_type.span(),
-                "mockall_derive does not support this type as a return argument");
+            return Err(Error::new(literal_type.span(),
+                "mockall_derive does not support this type as a return argument"));
         },
         Type::TraitObject(tto) => {
             // Change types like `dyn Self` into `dyn MockXXX`.
             for bound in tto.bounds.iter_mut() {
                 if let TypeParamBound::Trait(t) = bound {
-                    deselfify_path(&mut t.path, actual, generics);
+                    deselfify_path(&mut t.path, actual, generics)?;
                 }
             }
         },
@@ -600,8 +641,9 @@ fn deselfify(literal_type: &mut Type, actual: &Ident, generics: &Generics) {
         {
             /* Nothing to do */
         },
-        _ => compile_error(literal_type.span(), "Unsupported type"),
+        _ => return Err(Error::new(literal_type.span(), "Unsupported type")),
     }
+    Ok(())
 }
 
 /// Change any `Self` in a method's arguments' types with `actual`.
@@ -609,45 +651,50 @@ fn deselfify(literal_type: &mut Type, actual: &Ident, generics: &Generics) {
 fn deselfify_args(
     args: &mut Punctuated<FnArg, Token![,]>,
     actual: &Ident,
-    generics: &Generics)
+    generics: &Generics) -> Result<()>
 {
+    let mut errors = Errors::default();
     for arg in args.iter_mut() {
-        match arg {
+        let r = match arg {
             FnArg::Receiver(r) => {
                 if r.colon_token.is_some() {
                     deselfify(r.ty.as_mut(), actual, generics)
+                } else {
+                    Ok(())
                 }
             },
             FnArg::Typed(pt) => deselfify(pt.ty.as_mut(), actual, generics)
-        }
+        };
+        errors.ok(r);
     }
+    errors.finish(())
 }
 
+/// Find the name and generic arguments of the type or trait named by `path`.
+///
+/// The path may refer to an item in another module, like `crate::a::Foo<T>`.
 fn find_ident_from_path(path: &Path) -> (Ident, PathArguments) {
-    if path.segments.len() != 1 {
-        compile_error(path.span(),
-            "mockall_derive only supports structs defined in the current module");
-        return (Ident::new("", path.span()), PathArguments::None);
-    }
     let last_seg = path.segments.last().unwrap();
     (last_seg.ident.clone(), last_seg.arguments.clone())
 }
 
-fn find_lifetimes_in_tpb(bound: &TypeParamBound) -> HashSet<Lifetime> {
+fn find_lifetimes_in_tpb(bound: &TypeParamBound)
+    -> Result<HashSet<Lifetime>>
+{
     let mut ret = HashSet::default();
     match bound {
         TypeParamBound::Lifetime(lt) => {
             ret.insert(lt.clone());
         },
         TypeParamBound::Trait(tb) => {
-            ret.extend(find_lifetimes_in_path(&tb.path));
+            ret.extend(find_lifetimes_in_path(&tb.path)?);
         },
         _ => ()
     };
-    ret
+    Ok(ret)
 }
 
-fn find_lifetimes_in_path(path: &Path) -> HashSet<Lifetime> {
+fn find_lifetimes_in_path(path: &Path) -> Result<HashSet<Lifetime>> {
     let mut ret = HashSet::default();
     for seg in path.segments.iter() {
         if let PathArguments::AngleBracketed(abga) = &seg.arguments {
@@ -657,14 +704,14 @@ fn find_lifetimes_in_path(path: &Path) -> HashSet<Lifetime> {
                         ret.insert(lt.clone());
                     },
                     GenericArgument::Type(ty) => {
-                        ret.extend(find_lifetimes(ty));
+                        ret.extend(find_lifetimes(ty)?);
                     },
                     GenericArgument::AssocType(at) => {
-                        ret.extend(find_lifetimes(&at.ty));
+                        ret.extend(find_lifetimes(&at.ty)?);
                     },
                     GenericArgument::Constraint(c) => {
                         for bound in c.bounds.iter() {
-                            ret.extend(find_lifetimes_in_tpb(bound));
+                            ret.extend(find_lifetimes_in_tpb(bound)?);
                         }
                     },
                     GenericArgument::Const(_) => (),
@@ -673,57 +720,54 @@ fn find_lifetimes_in_path(path: &Path) -> HashSet<Lifetime> {
             }
         }
     }
-    ret
+    Ok(ret)
 }
 
-fn find_lifetimes(ty: &Type) -> HashSet<Lifetime> {
+fn find_lifetimes(ty: &Type) -> Result<HashSet<Lifetime>> {
     match ty {
         Type::Array(ta) => find_lifetimes(ta.elem.as_ref()),
         Type::Group(tg) => find_lifetimes(tg.elem.as_ref()),
-        Type::Infer(_ti) => HashSet::default(),
-        Type::Never(_tn) => HashSet::default(),
+        Type::Infer(_ti) => Ok(HashSet::default()),
+        Type::Never(_tn) => Ok(HashSet::default()),
         Type::Paren(tp) => find_lifetimes(tp.elem.as_ref()),
         Type::Path(tp) => {
-            let mut ret = find_lifetimes_in_path(&tp.path);
+            let mut ret = find_lifetimes_in_path(&tp.path)?;
             if let Some(qs) = &tp.qself {
-                ret.extend(find_lifetimes(qs.ty.as_ref()));
+                ret.extend(find_lifetimes(qs.ty.as_ref())?);
             }
-            ret
+            Ok(ret)
         },
         Type::Ptr(tp) => find_lifetimes(tp.elem.as_ref()),
         Type::Reference(tr) => {
-            let mut ret = find_lifetimes(tr.elem.as_ref());
+            let mut ret = find_lifetimes(tr.elem.as_ref())?;
             if let Some(lt) = &tr.lifetime {
                 ret.insert(lt.clone());
             }
-            ret
+            Ok(ret)
         },
         Type::Slice(ts) => find_lifetimes(ts.elem.as_ref()),
         Type::TraitObject(tto) => {
             let mut ret = HashSet::default();
             for bound in tto.bounds.iter() {
-                ret.extend(find_lifetimes_in_tpb(bound));
+                ret.extend(find_lifetimes_in_tpb(bound)?);
             }
-            ret
+            Ok(ret)
         }
         Type::Tuple(tt) => {
             let mut ret = HashSet::default();
             for ty in tt.elems.iter() {
-                ret.extend(find_lifetimes(ty));
+                ret.extend(find_lifetimes(ty)?);
             }
-            ret
+            Ok(ret)
         },
         Type::ImplTrait(tit) => {
             let mut ret = HashSet::default();
             for tpb in tit.bounds.iter() {
-                ret.extend(find_lifetimes_in_tpb(tpb));
+                ret.extend(find_lifetimes_in_tpb(tpb)?);
             }
-            ret
+            Ok(ret)
         },
-        _ => {
-            compile_error(ty.span(), "unsupported type in this context");
-            HashSet::default()
-        }
+        _ => Err(Error::new(ty.span(), "unsupported type in this context"))
     }
 }
 
@@ -804,6 +848,37 @@ impl<'a> AttrFormatter<'a> {
     }
 }
 
+/// Does this type contain any reference, lifetime, or `impl Trait`?
+///
+/// A conservative check, done on the type's tokens.  A value of such a type
+/// can't be kept in a call history, and might not outlive a lock guard that it
+/// was obtained through.  Even `'static` lifetimes count, because Mockall may
+/// have substituted them for anonymous ones.
+fn type_borrows(ty: &Type) -> bool {
+    fn tokens_borrow(ts: TokenStream) -> bool {
+        ts.into_iter().any(|tt| match tt {
+            proc_macro2::TokenTree::Group(g) => tokens_borrow(g.stream()),
+            proc_macro2::TokenTree::Ident(i) => i == "impl",
+            proc_macro2::TokenTree::Punct(p) =>
+                p.as_char() == '&' || p.as_char() == '\'',
+            proc_macro2::TokenTree::Literal(_) => false
+        })
+    }
+    tokens_borrow(ty.to_token_stream())
+}
+
+/// Does this type's definition mention the given identifier anywhere?
+fn type_mentions(ty: &Type, ident: &Ident) -> bool {
+    fn tokens_mention(ts: TokenStream, ident: &Ident) -> bool {
+        ts.into_iter().any(|tt| match tt {
+            proc_macro2::TokenTree::Group(g) => tokens_mention(g.stream(), ident),
+            proc_macro2::TokenTree::Ident(i) => i == *ident,
+            _ => false
+        })
+    }
+    tokens_mention(ty.to_token_stream(), ident)
+}
+
 /// Determine if this Pat is any kind of `self` binding
 fn pat_is_self(pat: &Pat) -> bool {
     if let Pat::Ident(pi) = pat {
@@ -814,7 +889,7 @@ fn pat_is_self(pat: &Pat) -> bool {
 }
 
 /// Add `levels` `super::` to the path.  Return the number of levels added.
-fn supersuperfy_path(path: &mut Path, levels: usize) -> usize {
+fn supersuperfy_path(path: &mut Path, levels: usize) -> Result<usize> {
     if let Some(t) = path.segments.last_mut() {
         match &mut t.arguments {
             PathArguments::None => (),
@@ -822,13 +897,14 @@ fn supersuperfy_path(path: &mut Path, levels: usize) -> usize {
                 for arg in abga.args.iter_mut() {
                     match arg {
                         GenericArgument::Type(ref mut ty) => {
-                            *ty = supersuperfy(ty, levels);
+                            *ty = supersuperfy(ty, levels)?;
                         },
                         GenericArgument::AssocType(ref mut at) => {
-                            at.ty = supersuperfy(&at.ty, levels);
+                            at.ty = supersuperfy(&at.ty, levels)?;
                         },
                         GenericArgument::Constraint(ref mut constraint) => {
-                            supersuperfy_bounds(&mut constraint.bounds, levels);
+                            supersuperfy_bounds(&mut constraint.bounds,
+                                                levels)?;
                         },
                         _ => (),
                     }
@@ -836,10 +912,10 @@ fn supersuperfy_path(path: &mut Path, levels: usize) -> usize {
             },
             PathArguments::Parenthesized(ref mut pga) => {
                 for input in pga.inputs.iter_mut() {
-                    *input = supersuperfy(input, levels);
+                    *input = supersuperfy(input, levels)?;
                 }
                 if let ReturnType::Type(_, ref mut ty) = pga.output {
-                    *ty = Box::new(supersuperfy(ty, levels));
+                    **ty = supersuperfy(ty, levels)?;
                 }
             },
         }
@@ -855,66 +931,66 @@ fn supersuperfy_path(path: &mut Path, levels: usize) -> usize {
             for _ in 0..levels {
                 path.segments.insert(0, ps.clone());
             }
-            levels
+            Ok(levels)
         } else {
-            0
+            Ok(0)
         }
     } else {
-        0
+        Ok(0)
     }
 }
 
 /// Replace any references to `super::X` in `original` with `super::super::X`.
-fn supersuperfy(original: &Type, levels: usize) -> Type {
+fn supersuperfy(original: &Type, levels: usize) -> Result<Type> {
     let mut output = original.clone();
-    fn recurse(t: &mut Type, levels: usize) {
+    fn recurse(t: &mut Type, levels: usize) -> Result<()> {
         match t {
             Type::Slice(s) => {
-                recurse(s.elem.as_mut(), levels);
+                recurse(s.elem.as_mut(), levels)?;
             },
             Type::Array(a) => {
-                recurse(a.elem.as_mut(), levels);
+                recurse(a.elem.as_mut(), levels)?;
             },
             Type::Ptr(p) => {
-                recurse(p.elem.as_mut(), levels);
+                recurse(p.elem.as_mut(), levels)?;
             },
             Type::Reference(r) => {
-                recurse(r.elem.as_mut(), levels);
+                recurse(r.elem.as_mut(), levels)?;
             },
             Type::BareFn(bfn) => {
                 if let ReturnType::Type(_, ref mut bt) = bfn.output {
-                    recurse(bt.as_mut(), levels);
+                    recurse(bt.as_mut(), levels)?;
                 }
                 for input in bfn.inputs.iter_mut() {
-                    recurse(&mut input.ty, levels);
+                    recurse(&mut input.ty, levels)?;
                 }
             },
             Type::Tuple(tuple) => {
                 for elem in tuple.elems.iter_mut() {
-                    recurse(elem, levels);
+                    recurse(elem, levels)?;
                 }
             }
             Type::Path(type_path) => {
-                let added = supersuperfy_path(&mut type_path.path, levels);
+                let added = supersuperfy_path(&mut type_path.path, levels)?;
                 if let Some(ref mut qself) = type_path.qself {
-                    recurse(qself.ty.as_mut(), levels);
+                    recurse(qself.ty.as_mut(), levels)?;
                     qself.position += added;
                 }
             },
             Type::Paren(p) => {
-                recurse(p.elem.as_mut(), levels);
+                recurse(p.elem.as_mut(), levels)?;
             },
             Type::Group(g) => {
-                recurse(g.elem.as_mut(), levels);
+                recurse(g.elem.as_mut(), levels)?;
             },
             Type::Macro(_) | Type::Verbatim(_) => {
-                compile_error(t.span(),
-                    "mockall_derive does not support this type in this position");
+                return Err(Error::new(t.span(),
+                    "mockall_derive does not support this type in this position"));
             },
             Type::TraitObject(tto) => {
                 for bound in tto.bounds.iter_mut() {
                     if let TypeParamBound::Trait(tb) = bound {
-                        supersuperfy_path(&mut tb.path, levels);
+                        supersuperfy_path(&mut tb.path, levels)?;
                     }
                 }
             },
@@ -925,48 +1001,64 @@ fn supersuperfy(original: &Type, levels: usize) -> Type {
             {
                 /* Nothing to do */
             },
-            _ => compile_error(t.span(), "Unsupported type"),
+            _ => return Err(Error::new(t.span(), "Unsupported type")),
         }
+        Ok(())
     }
-    recurse(&mut output, levels);
-    output
+    recurse(&mut output, levels)?;
+    Ok(output)
 }
 
-fn supersuperfy_generics(generics: &mut Generics, levels: usize) {
+fn supersuperfy_generics(generics: &mut Generics, levels: usize) -> Result<()> {
     for param in generics.params.iter_mut() {
         if let GenericParam::Type(tp) = param {
-            supersuperfy_bounds(&mut tp.bounds, levels);
+            supersuperfy_bounds(&mut tp.bounds, levels)?;
             if let Some(ty) = tp.default.as_mut() {
-                *ty = supersuperfy(ty, levels);
+                *ty = supersuperfy(ty, levels)?;
             }
         }
     }
     if let Some(wc) = generics.where_clause.as_mut() {
         for wp in wc.predicates.iter_mut() {
             if let WherePredicate::Type(pt) = wp {
-                pt.bounded_ty = supersuperfy(&pt.bounded_ty, levels);
-                supersuperfy_bounds(&mut pt.bounds, levels);
+                pt.bounded_ty = supersuperfy(&pt.bounded_ty, levels)?;
+                supersuperfy_bounds(&mut pt.bounds, levels)?;
             }
         }
     }
+    Ok(())
 }
 
 fn supersuperfy_bounds(
     bounds: &mut Punctuated<TypeParamBound, Token![+]>,
-    levels: usize)
+    levels: usize) -> Result<()>
 {
     for bound in bounds.iter_mut() {
         if let TypeParamBound::Trait(tb) = bound {
-            supersuperfy_path(&mut tb.path, levels);
+            supersuperfy_path(&mut tb.path, levels)?;
         }
     }
+    Ok(())
 }
 
 /// Generate a suitable mockall::Key generic paramter from any Generics
+///
+/// `mockall::Key` can only be keyed by types, so any const generic parameters
+/// are combined into a single `ConstParams` type, which must be defined by
+/// [`gen_const_params`].
 fn gen_keyid(g: &Generics) -> impl ToTokens {
+    let consts = g.const_params()
+        .map(|cp| cp.ident.clone())
+        .collect::<Punctuated::<Ident, Token![,]>>();
+    let mut tps = g.type_params()
+        .map(|tp| tp.ident.to_token_stream())
+        .collect::<Punctuated::<TokenStream, Token![,]>>();
+    if !consts.is_empty() {
+        tps.push(quote!(ConstParams<#consts>));
+    }
     match g.params.len() {
         0 => quote!(<()>),
-        1 => {
+        1 if consts.is_empty() => {
             let (_, tg, _) = g.split_for_impl();
             quote!(#tg)
         },
@@ -974,19 +1066,49 @@ fn gen_keyid(g: &Generics) -> impl ToTokens {
             // Rust doesn't support variadic Generics, so mockall::Key must
             // always have exactly one generic type.  We need to add parentheses
             // around whatever type generics the caller passes.
-            let tps = g.type_params()
-            .map(|tp| tp.ident.clone())
-            .collect::<Punctuated::<Ident, Token![,]>>();
             quote!(<(#tps)>)
         }
     }
 }
 
+/// Define the `ConstParams` type used by [`gen_keyid`], if there are any const
+/// generic parameters.
+fn gen_const_params(g: &Generics) -> impl ToTokens {
+    let consts = g.const_params()
+        .map(|cp| {
+            let mut cp = cp.clone();
+            cp.default = None;
+            cp.eq_token = None;
+            cp
+        }).collect::<Punctuated::<ConstParam, Token![,]>>();
+    if consts.is_empty() {
+        quote!()
+    } else {
+        quote!(
+            /// Represents the const generic parameters in a `mockall::Key`
+            #[doc(hidden)]
+            pub struct ConstParams<#consts>;
+        )
+    }
+}
+
 /// Generate a mock identifier from the regular one: eg "Foo" => "MockFoo"
 fn gen_mock_ident(ident: &Ident) -> Ident {
     format_ident!("Mock{}", ident)
 }
 
+/// Generate an identifier for the helper trait that holds a trait's default
+/// method implementations: eg "Foo" => "__mock_Foo_defaults"
+fn gen_default_trait_ident(trait_: &Ident) -> Ident {
+    format_ident!("__mock_{}_defaults", trait_)
+}
+
+/// Generate an identifier for a copy of a trait's default method
+/// implementation: eg "foo" => "__mockall_default_foo"
+fn gen_default_fn_ident(meth: &Ident) -> Ident {
+    format_ident!("__mockall_default_{}", meth)
+}
+
 /// Generate an identifier for the mock struct's private module: eg "Foo" =>
 /// "__mock_Foo"
 fn gen_mod_ident(struct_: &Ident, trait_: Option<&Ident>) -> Ident {
@@ -1117,16 +1239,17 @@ fn lifetimes_to_generics(lv: &Punctuated<LifetimeParam, Token![,]>)-> Generics {
 /// Split a generics list into three: one for type generics and where predicates
 /// that relate to the signature, one for lifetimes that relate to the arguments
 /// only, and one for lifetimes that relate to the return type only.
+#[allow(clippy::type_complexity)]
 fn split_lifetimes(
     generics: Generics,
     args: &[FnArg],
     rt: &ReturnType)
-    -> (Generics,
+    -> Result<(Generics,
         Punctuated<LifetimeParam, token::Comma>,
-        Punctuated<LifetimeParam, token::Comma>)
+        Punctuated<LifetimeParam, token::Comma>)>
 {
     if generics.lt_token.is_none() {
-        return (generics, Default::default(), Default::default());
+        return Ok((generics, Default::default(), Default::default()));
     }
 
     // Check which types and lifetimes are referenced by the arguments
@@ -1140,13 +1263,13 @@ fn split_lifetimes(
                 }
             },
             FnArg::Typed(pt) => {
-                alts.extend(find_lifetimes(pt.ty.as_ref()));
+                alts.extend(find_lifetimes(pt.ty.as_ref())?);
             },
         };
     };
 
     if let ReturnType::Type(_, ty) = rt {
-        rlts.extend(find_lifetimes(ty));
+        rlts.extend(find_lifetimes(ty)?);
     }
 
     let mut tv = Punctuated::new();
@@ -1162,8 +1285,7 @@ fn split_lifetimes(
                 // Probably a lifetime parameter from the impl block that isn't
                 // used by this particular method
             },
-            GenericParam::Type(_) => tv.push(p),
-            _ => (),
+            GenericParam::Type(_) | GenericParam::Const(_) => tv.push(p),
         }
     }
 
@@ -1178,7 +1300,7 @@ fn split_lifetimes(
         }
     };
 
-    (tg, alv, rlv)
+    Ok((tg, alv, rlv))
 }
 
 /// Return the visibility that should be used for expectation!, given the
@@ -1239,15 +1361,14 @@ fn staticize(generics: &Generics) -> Generics {
     ret
 }
 
-fn mock_it<M: Into<MockableItem>>(inputs: M) -> TokenStream
+fn mock_it(mockable: MockableItem) -> Result<TokenStream>
 {
-    let mockable: MockableItem = inputs.into();
-    let mock = MockItem::from(mockable);
+    let mock = MockItem::try_from(mockable)?;
     let ts = mock.into_token_stream();
     if env::var("MOCKALL_DEBUG").is_ok() {
         println!("{ts}");
     }
-    ts
+    Ok(ts)
 }
 
 fn do_mock_once(input: TokenStream) -> TokenStream
@@ -1258,7 +1379,7 @@ fn do_mock_once(input: TokenStream) -> TokenStream
             return err.to_compile_error();
         }
     };
-    mock_it(item)
+    mock_it(item.into()).unwrap_or_else(Error::into_compile_error)
 }
 
 fn do_mock(input: TokenStream) -> TokenStream
@@ -1273,6 +1394,36 @@ fn do_mock(input: TokenStream) -> TokenStream
     do_mock_once(input)
 }
 
+fn do_mock_module_once(input: TokenStream) -> TokenStream
+{
+    let item: ItemMod = match parse2(input) {
+        Ok(item) => item,
+        Err(err) => {
+            return err.to_compile_error();
+        }
+    };
+    if let Some((brace, _)) = &item.content {
+        return Error::new(brace.span.join(),
+            "mock_module! only mocks out-of-line modules.  Use #[automock] on inline modules instead")
+            .to_compile_error();
+    }
+    MockableItem::try_from((Attrs::default(), Item::Mod(item)))
+        .and_then(mock_it)
+        .unwrap_or_else(Error::into_compile_error)
+}
+
+fn do_mock_module(input: TokenStream) -> TokenStream
+{
+    cfg_if! {
+        if #[cfg(reprocheck)] {
+            let ts_a = do_mock_module_once(input.clone());
+            let ts_b = do_mock_module_once(input.clone());
+            assert_eq!(ts_a.to_string(), ts_b.to_string());
+        }
+    }
+    do_mock_module_once(input)
+}
+
 #[proc_macro_attribute]
 pub fn concretize(
     _attrs: proc_macro::TokenStream,
@@ -1288,6 +1439,11 @@ pub fn mock(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     do_mock(input.into()).into()
 }
 
+#[proc_macro]
+pub fn mock_module(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    do_mock_module(input.into()).into()
+}
+
 #[proc_macro_attribute]
 pub fn automock(attrs: proc_macro::TokenStream, input: proc_macro::TokenStream)
     -> proc_macro::TokenStream
@@ -1311,7 +1467,9 @@ fn do_automock_once(attrs: TokenStream, input: TokenStream) -> TokenStream {
             return err.to_compile_error();
         }
     };
-    output.extend(mock_it((attrs, item)));
+    output.extend(MockableItem::try_from((attrs, item))
+        .and_then(mock_it)
+        .unwrap_or_else(Error::into_compile_error));
     output
 }
 
@@ -1419,6 +1577,23 @@ mod mock {
 
 /// Various tests for overall code generation that are hard or impossible to
 /// write as integration tests
+mod mock_module {
+    use std::str::FromStr;
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn inline_module() {
+        let code = "mod foo { pub fn bar() {} }";
+        let ts = proc_macro2::TokenStream::from_str(code).unwrap();
+        let output = do_mock_module(ts).to_string();
+        assert_contains(&output, quote!(::core::compile_error! {
+            "mock_module! only mocks out-of-line modules.  Use #[automock] on inline modules instead"
+        }));
+        assert_not_contains(&output, quote!(mod mock_foo));
+    }
+}
+
 mod automock {
     use std::str::FromStr;
     use super::super::*;
@@ -1507,12 +1682,66 @@ mod automock {
     }
 
     #[test]
-    #[should_panic(expected = "can only mock inline modules")]
-    fn external_module() {
+    fn external_module_not_found() {
+        let code = r#"#[path = "src/nonexistent.rs"] mod foo;"#;
+        let ts = proc_macro2::TokenStream::from_str(code).unwrap();
+        let attrs_ts = proc_macro2::TokenStream::from_str("").unwrap();
+        let output = do_automock(attrs_ts, ts).to_string();
+        assert_contains(&output, quote!(mod foo;));
+        assert_contains(&output, quote!(::core::compile_error! {
+            "automock could not find the source file for module `foo`"
+        }));
+    }
+
+    /// Outside of a real macro invocation, the caller's file is unknown, as
+    /// with compilers older than 1.88.  Then a #[path] attribute is required.
+    #[test]
+    fn external_module_unknown_caller() {
         let code = "mod foo;";
         let ts = proc_macro2::TokenStream::from_str(code).unwrap();
         let attrs_ts = proc_macro2::TokenStream::from_str("").unwrap();
-        do_automock(attrs_ts, ts).to_string();
+        let output = do_automock(attrs_ts, ts).to_string();
+        assert_contains(&output, quote!(::core::compile_error! {
+            "mockall can't tell which file declares module `foo`, because this compiler is older than 1.88.  Add a #[path] attribute, relative to the package's root directory"
+        }));
+    }
+
+    /// The mock is named after only the last segment of the struct's path,
+    /// so mark it with an item that collides if another mock has that name.
+    #[test]
+    fn path_self_ty() {
+        let code = "impl a::Foo { fn foo(&self) {} }";
+        let ts = proc_macro2::TokenStream::from_str(code).unwrap();
+        let attrs_ts = proc_macro2::TokenStream::from_str("").unwrap();
+        let output = do_automock(attrs_ts, ts).to_string();
+        assert_contains(&output, quote!(struct MockFoo));
+        assert_contains(&output,
+            quote!(const __mockall_MockFoo_is_already_defined_here: () = ();));
+    }
+
+    #[test]
+    fn unsupported_item() {
+        let code = "pub struct Foo;";
+        let ts = proc_macro2::TokenStream::from_str(code).unwrap();
+        let attrs_ts = proc_macro2::TokenStream::from_str("").unwrap();
+        let output = do_automock(attrs_ts, ts).to_string();
+        assert_contains(&output, quote!(pub struct Foo;));
+        assert_contains(&output, quote!(::core::compile_error! {
+            "automock does not support this item type"
+        }));
+    }
+
+    /// The shared handle documents the traits that it can't implement
+    #[test]
+    fn unshareable_trait() {
+        let code = "trait Foo { fn foo(&self) -> &u32; }";
+        let ts = proc_macro2::TokenStream::from_str(code).unwrap();
+        let attrs_ts = proc_macro2::TokenStream::from_str("").unwrap();
+        let output = do_automock(attrs_ts, ts).to_string();
+        assert!(output.contains(
+            "It doesn't implement `Foo`, because it can't forward the `foo` method."
+        ));
+        assert_not_contains(&output, quote!(impl Foo for SharedMockFoo));
     }
 
     #[test]
@@ -1537,7 +1766,7 @@ mod concretize_args {
     {
         let f: Signature = parse2(sig).unwrap();
         let (generics, inputs, call_exprs) =
-            concretize_args(&f.generics, &f.inputs);
+            concretize_args(&f.generics, &f.inputs).unwrap();
         assert!(generics.params.is_empty());
         assert_eq!(inputs.len(), expected_inputs.len());
         assert_eq!(call_exprs.len(), expected_call_exprs.len());
@@ -1703,7 +1932,7 @@ mod deselfify {
         let actual: Ident = parse2(actual_ts).unwrap();
         let generics: Generics = parse2(generics_ts).unwrap();
         let expected: Type = parse2(expected_ts).unwrap();
-        deselfify(&mut ty, &actual, &generics);
+        deselfify(&mut ty, &actual, &generics).unwrap();
         assert_eq!(quote!(#ty).to_string(),
                    quote!(#expected).to_string());
     }
@@ -1915,7 +2144,7 @@ mod supersuperfy {
     fn check_supersuperfy(orig: TokenStream, expected: TokenStream) {
         let orig_ty: Type = parse2(orig).unwrap();
         let expected_ty: Type = parse2(expected).unwrap();
-        let output = supersuperfy(&orig_ty, 1);
+        let output = supersuperfy(&orig_ty, 1).unwrap();
         assert_eq!(quote!(#output).to_string(),
                    quote!(#expected_ty).to_string());
     }
@@ -1946,7 +2175,7 @@ mod supersuperfy {
             group_token: token::Group::default(),
             elem: Box::new(parse2(quote!(super::super::T)).unwrap())
         };
-        let output = supersuperfy(&Type::Group(orig), 1);
+        let output = supersuperfy(&Type::Group(orig), 1).unwrap();
         assert_eq!(quote!(#output).to_string(),
                    quote!(#expected).to_string());
     }
@@ -2050,7 +2279,7 @@ mod supersuperfy_generics {
         let mut expected_g: Generics = parse2(expected).unwrap();
         expected_g.where_clause = parse2(expected_wc).unwrap();
         let mut output: Generics = orig_g;
-        supersuperfy_generics(&mut output, 1);
+        supersuperfy_generics(&mut output, 1).unwrap();
         let (o_ig, o_tg, o_wc) = output.split_for_impl();
         let (e_ig, e_tg, e_wc) = expected_g.split_for_impl();
         assert_eq!(quote!(#o_ig).to_string(), quote!(#e_ig).to_string());
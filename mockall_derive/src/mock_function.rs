@@ -1,5 +1,5 @@
 // vim: tw=80
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
 use quote::{ToTokens, format_ident, quote};
 use syn::{
     *,
@@ -11,10 +11,11 @@ use syn::{
 use crate::{
     AttrFormatter,
     HashSet,
-    compile_error,
     concretize_args,
     declosurefy,
     expectation_visibility,
+    gen_const_params,
+    gen_default_fn_ident,
     gen_keyid,
     is_concretize,
     lifetimes_to_generic_params,
@@ -25,6 +26,8 @@ use crate::{
     staticize,
     supersuperfy,
     supersuperfy_generics,
+    type_borrows,
+    type_mentions,
 };
 
 /// Convert a trait object reference into a reference to a Boxed trait
@@ -121,10 +124,53 @@ fn destrify(ty: &mut Type) {
     }
 }
 
+/// Replace any of the struct's lifetime parameters within a return type with
+/// `'static`.
+///
+/// The Expectation must be `'static`, so it can't hold anything with the
+/// struct's lifetimes.  But a `'static` value can always be returned where one
+/// with a shorter lifetime is expected.
+fn staticize_struct_lifetimes(ty: &mut Type, struct_generics: &Generics) {
+    fn staticize_ts(ts: TokenStream, lts: &HashSet<Ident>) -> TokenStream {
+        let mut tick = false;
+        ts.into_iter().map(|tt| {
+            let r = match tt {
+                TokenTree::Group(g) => {
+                    let mut g2 = Group::new(g.delimiter(),
+                        staticize_ts(g.stream(), lts));
+                    g2.set_span(g.span());
+                    TokenTree::Group(g2)
+                },
+                TokenTree::Ident(i) if tick && lts.contains(&i) =>
+                    TokenTree::Ident(Ident::new("static", i.span())),
+                tt => tt
+            };
+            tick = matches!(&r, TokenTree::Punct(p) if p.as_char() == '\'');
+            r
+        }).collect()
+    }
+
+    let lts = struct_generics.lifetimes()
+        .map(|ltp| ltp.lifetime.ident.clone())
+        .collect::<HashSet<_>>();
+    if !lts.is_empty() {
+        *ty = parse2(staticize_ts(ty.to_token_stream(), &lts)).unwrap();
+    }
+}
+
+/// Strip any bounds from a list of lifetime parameters
+fn unbound_lifetimes(lv: Punctuated<LifetimeParam, Token![,]>)
+    -> Punctuated<LifetimeParam, Token![,]>
+{
+    lv.into_iter()
+        .map(|ltp| LifetimeParam::new(ltp.lifetime))
+        .collect()
+}
+
 /// Return the owned version of the input.
 fn ownify(ty: &Type) -> Type {
     if let Type::Reference(ref tr) = &ty {
-        if tr.lifetime.as_ref().map_or(false, |lt| lt.ident == "static")
+        if tr.lifetime.as_ref().is_some_and(|lt| lt.ident == "static")
         {
             // Just a static expectation
             ty.clone()
@@ -136,6 +182,38 @@ fn ownify(ty: &Type) -> Type {
     }
 }
 
+/// Return the owned type used to record an argument of type `ty` in the mock
+/// method's call history, or `None` if such an argument can't be recorded.
+fn recordify(ty: &Type) -> Option<Type> {
+    let mut rty = if let Type::Reference(tr) = ty {
+        // Record referenced arguments by their owned types, like
+        // "&str" => "String"
+        let mut tr2 = Type::Reference(TypeReference {
+            and_token: tr.and_token,
+            lifetime: None,
+            mutability: None,
+            elem: tr.elem.clone()
+        });
+        destrify(&mut tr2);
+        if let Type::Reference(tr2) = tr2 {
+            *tr2.elem
+        } else {
+            unreachable!()
+        }
+    } else {
+        ty.clone()
+    };
+    while let Type::Paren(tp) = rty {
+        rty = *tp.elem;
+    }
+    match rty {
+        Type::TraitObject(_) | Type::ImplTrait(_) | Type::Slice(_) => None,
+        // Borrowed values may not be stored in the call history
+        _ if type_borrows(&rty) => None,
+        _ => Some(rty)
+    }
+}
+
 /// Add Send + Sync to a where clause
 fn send_syncify(wc: &mut Option<WhereClause>, bounded_ty: Type) {
     let mut bounds = Punctuated::new();
@@ -176,11 +254,13 @@ pub(crate) struct Builder<'a> {
     attrs: &'a [Attribute],
     call_levels: Option<usize>,
     concretize: bool,
+    default_impl: Option<&'a Path>,
     levels: usize,
     parent: Option<&'a Ident>,
     sig: &'a Signature,
     struct_: Option<&'a Ident>,
     struct_generics: Option<&'a Generics>,
+    thread_local: bool,
     trait_: Option<&'a Ident>,
     vis: &'a Visibility
 }
@@ -196,19 +276,24 @@ impl<'a> Builder<'a> {
         self
     }
 
-    pub fn build(self) -> MockFunction {
+    pub fn build(self) -> Result<MockFunction> {
         let mut argnames = Vec::new();
         let mut argty = Vec::new();
+        let call_levels = self.call_levels.unwrap_or(self.levels);
+        let mut call_recty = Vec::new();
         let mut is_static = true;
         let mut predexprs = Vec::new();
         let mut predty = Vec::new();
+        let mut default_recexprs = Vec::new();
+        let mut recexprs = Vec::new();
+        let mut recty = Vec::new();
         let mut refpredty = Vec::new();
 
         let (mut declosured_generics, declosured_inputs, call_exprs) =
             if self.concretize {
-                concretize_args(&self.sig.generics, &self.sig.inputs)
+                concretize_args(&self.sig.generics, &self.sig.inputs)?
             } else {
-                declosurefy(&self.sig.generics, &self.sig.inputs)
+                declosurefy(&self.sig.generics, &self.sig.inputs)?
             };
         // TODO: make concretize and declosurefy work for the same function
 
@@ -216,7 +301,30 @@ impl<'a> Builder<'a> {
             if let FnArg::Typed(pt) = fa {
                 let argname = (*pt.pat).clone();
                 assert!(!pat_is_self(&argname));
-                let aty = supersuperfy(&pt.ty, self.levels);
+                let aty = supersuperfy(&pt.ty, self.levels)?;
+                if let Some(rty) = recordify(&pt.ty) {
+                    let predexpr = if let Type::Reference(_) = aty {
+                        quote!(#argname)
+                    } else {
+                        quote!(&#argname)
+                    };
+                    recexprs.push(quote!(
+                        (&&::mockall::ArgRecorder(#predexpr)).record()
+                    ));
+                    default_recexprs.push(quote!(
+                        (&&::mockall::ArgRecorder(#argname)).record()
+                    ));
+                    call_recty.push(supersuperfy(&rty, call_levels)?);
+                    recty.push(supersuperfy(&rty, self.levels)?);
+                } else {
+                    let unit: Type = parse2(quote!(())).unwrap();
+                    recexprs.push(quote!(::mockall::ArgRecord::default()));
+                    default_recexprs.push(
+                        quote!(::mockall::ArgRecord::default())
+                    );
+                    call_recty.push(unit.clone());
+                    recty.push(unit);
+                }
                 if let Type::Reference(ref tr) = aty {
                     predexprs.push(quote!(#argname));
                     predty.push((*tr.elem).clone());
@@ -244,6 +352,8 @@ impl<'a> Builder<'a> {
                 is_static = false;
             }
         }
+        let struct_generics = self.struct_generics.cloned()
+            .unwrap_or_default();
         let (output, boxed) = match self.sig.output {
             ReturnType::Default => (
                 Type::Tuple(TypeTuple {
@@ -253,18 +363,19 @@ impl<'a> Builder<'a> {
                 false,
             ),
             ReturnType::Type(_, ref ty) => {
-                let mut output_ty = supersuperfy(ty, self.levels);
+                let mut output_ty = supersuperfy(ty, self.levels)?;
+                staticize_struct_lifetimes(&mut output_ty, &struct_generics);
                 destrify(&mut output_ty);
                 let boxed = dedynify(&mut output_ty);
                 (output_ty, boxed)
             }
         };
-        supersuperfy_generics(&mut declosured_generics, self.levels);
+        supersuperfy_generics(&mut declosured_generics, self.levels)?;
         let owned_output = ownify(&output);
         let mut return_ref = false;
         let mut return_refmut = false;
         if let Type::Reference(ref tr) = &output {
-            if tr.lifetime.as_ref().map_or(true, |lt| lt.ident != "static")
+            if !matches!(&tr.lifetime, Some(lt) if lt.ident == "static")
             {
                 if tr.mutability.is_none() {
                     return_ref = true;
@@ -274,24 +385,26 @@ impl<'a> Builder<'a> {
             }
         };
         if is_static && (return_ref || return_refmut) {
-            compile_error(self.sig.span(),
-                "Mockall cannot mock static methods that return non-'static references.  It's unclear what the return value's lifetime should be.");
+            return Err(Error::new(self.sig.span(),
+                "Mockall cannot mock static methods that return non-'static references.  It's unclear what the return value's lifetime should be."));
         }
-        let struct_generics = self.struct_generics.cloned()
-            .unwrap_or_default();
         let (type_generics, salifetimes, srlifetimes) = split_lifetimes(
             struct_generics.clone(),
             &declosured_inputs,
             &ReturnType::Type(<Token![->]>::default(),
                               Box::new(owned_output.clone()))
-        );
+        )?;
+        // The Expectation never relies on relationships between the struct's
+        // lifetimes, and the lifetimes that bounds refer to may not even be in
+        // scope there.
+        let salifetimes = unbound_lifetimes(salifetimes);
         let srltg = lifetimes_to_generics(&srlifetimes);
         let (call_generics, malifetimes, mrlifetimes) = split_lifetimes(
             declosured_generics,
             &declosured_inputs,
             &ReturnType::Type(<Token![->]>::default(),
                               Box::new(owned_output.clone()))
-        );
+        )?;
         let mrltg = lifetimes_to_generics(&mrlifetimes);
         let cgenerics = merge_generics(&type_generics, &call_generics);
         let egenerics = merge_generics(
@@ -306,17 +419,28 @@ impl<'a> Builder<'a> {
         let fn_params = egenerics.type_params()
             .map(|tp| tp.ident.clone())
             .collect();
-        let call_levels = self.call_levels.unwrap_or(self.levels);
 
-        MockFunction {
+        // The default implementation can only be called if the mock method
+        // doesn't need to alter its arguments before matching them.
+        let unaltered = call_exprs.iter()
+            .zip(argnames.iter())
+            .all(|(ce, an)| ce.to_string() == quote!(#an).to_string());
+        let default_impl = self.default_impl
+            .filter(|_| unaltered && !self.concretize)
+            .cloned();
+
+        Ok(MockFunction {
             alifetimes,
             argnames,
             argty,
             attrs: self.attrs.to_vec(),
             call_exprs,
             call_generics,
+            call_recty,
             call_vis: expectation_visibility(self.vis, call_levels),
             concretize: self.concretize,
+            default_impl,
+            default_recexprs,
             egenerics,
             cgenerics,
             fn_params,
@@ -327,16 +451,19 @@ impl<'a> Builder<'a> {
             boxed,
             predexprs,
             predty,
+            recexprs,
+            recty,
             refpredty,
             return_ref,
             return_refmut,
             sig: self.sig.clone(),
             struct_: self.struct_.cloned(),
             struct_generics,
+            thread_local: self.thread_local,
             trait_: self.trait_.cloned(),
             type_generics,
             privmod_vis: expectation_visibility(self.vis, self.levels)
-        }
+        })
     }
 
     /// How many levels of modules beneath the original function this one is
@@ -346,6 +473,13 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Supply the path of the helper trait that holds this method's default
+    /// implementation, if any.
+    pub fn default_impl(&mut self, path: &'a Path) -> &mut Self {
+        self.default_impl = Some(path);
+        self
+    }
+
     /// How many levels of modules beneath the original function this one's
     /// private module is nested.
     pub fn levels(&mut self, levels: usize) -> &mut Self {
@@ -361,12 +495,14 @@ impl<'a> Builder<'a> {
         Builder {
             attrs: &[],
             concretize: false,
+            default_impl: None,
             levels: 0,
             call_levels: None,
             parent: None,
             sig,
             struct_: None,
             struct_generics: None,
+            thread_local: false,
             trait_: None,
             vis
         }
@@ -390,6 +526,12 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Store a static method's expectations per thread instead of globally
+    pub fn thread_local(&mut self, thread_local: bool) -> &mut Self {
+        self.thread_local = thread_local;
+        self
+    }
+
     /// Supply the name of the method's trait, if any
     pub fn trait_(&mut self, ident: &'a Ident) -> &mut Self {
         self.trait_ = Some(ident);
@@ -412,10 +554,19 @@ pub(crate) struct MockFunction {
     call_exprs: Vec<TokenStream>,
     /// Generics used for the expectation call
     call_generics: Generics,
+    /// Like `recty`, but relative to the mock function itself rather than its
+    /// private module
+    call_recty: Vec<Type>,
     /// Visibility of the mock function itself
     call_vis: Visibility,
     /// Are we turning generic arguments into concrete trait objects?
     concretize: bool,
+    /// Path of the helper trait holding this method's default implementation,
+    /// if it can be called.
+    default_impl: Option<Path>,
+    /// Like `recexprs`, but for arguments that are already references to their
+    /// predicate types
+    default_recexprs: Vec<TokenStream>,
     /// Generics of the Expectation object
     egenerics: Generics,
     /// Generics of the Common object
@@ -440,6 +591,11 @@ pub(crate) struct MockFunction {
     /// Types used for Predicates.  Will be almost the same as args, but every
     /// type will be a non-reference type.
     predty: Vec<Type>,
+    /// Expressions that record the call arguments in the call history
+    recexprs: Vec<TokenStream>,
+    /// Types used to return recorded arguments from the call history.  Will
+    /// be `()` for any argument that can't be recorded.
+    recty: Vec<Type>,
     /// Does the function return a non-'static reference?
     return_ref: bool,
     /// Does the function return a mutable reference?
@@ -452,6 +608,8 @@ pub(crate) struct MockFunction {
     struct_: Option<Ident>,
     /// Generics of the parent structure
     struct_generics: Generics,
+    /// Are a static method's expectations stored per thread?
+    thread_local: bool,
     /// Name of this method's trait, if the method comes from a trait
     trait_: Option<Ident>,
     /// Type generics of the mock structure
@@ -520,11 +678,18 @@ impl MockFunction {
         } else {
             quote!()
         };
-        let call = if self.return_refmut {
+        let mut call = if self.return_refmut {
             Ident::new("call_mut", Span::call_site())
         } else {
             Ident::new("call", Span::call_site())
         };
+        let call_default = self.call_default(&tbf);
+        let mut call_exprs = quote!(#(#call_exprs,)*);
+        if self.default_impl.is_some() {
+            // Reuse the expectation that call_default already found
+            call = format_ident!("{call}_found");
+            call_exprs = quote!(__mockall_found, #call_exprs);
+        }
         let mut deref = quote!();
         if self.boxed {
             if self.return_ref {
@@ -544,17 +709,23 @@ impl MockFunction {
                     use ::mockall::{ViaDebug, ViaNothing};
                     let no_match_msg = #no_match_msg;
                     #deref {
-                        let __mockall_guard = #outer_mod_path::get_expectations()
+                        let __mockall_expectations =
+                            #outer_mod_path::get_expectations();
+                        let __mockall_guard = __mockall_expectations
                             .lock().unwrap();
+                        #call_default
                         /*
                          * TODO: catch panics, then gracefully release the mutex
                          * so it won't be poisoned.  This requires bounding any
                          * generic parameters with UnwindSafe
                          */
                         /* std::panic::catch_unwind(|| */
-                        __mockall_guard.#call #tbf(#(#call_exprs,)*)
+                        __mockall_guard.#call #tbf(#call_exprs)
                         /*)*/
-                    }.expect(&no_match_msg)
+                    }.unwrap_or_else(|__mockall_report|
+                        ::mockall::fail_fatal(
+                            ::mockall::FailureKind::UnexpectedCall,
+                            std::format!("{}{}", no_match_msg, __mockall_report)))
                 }
             )
         } else {
@@ -565,15 +736,164 @@ impl MockFunction {
                 #no_mangle
                 #vis #sig {
                     use ::mockall::{ViaDebug, ViaNothing};
+                    #call_default
                     let no_match_msg = #no_match_msg;
-                    #deref self.#substruct_obj #name.#call #tbf(#(#call_exprs,)*)
-                    .expect(&no_match_msg)
+                    #deref self.#substruct_obj #name.#call #tbf(#call_exprs)
+                    .unwrap_or_else(|__mockall_report|
+                        ::mockall::fail_fatal(
+                            ::mockall::FailureKind::UnexpectedCall,
+                            std::format!("{}{}", no_match_msg, __mockall_report)))
                 }
 
             )
         }
     }
 
+    /// Generate a code fragment that will find the expectation for a call,
+    /// and call the trait's default implementation of this method if that
+    /// expectation says to.  Static methods must already hold the
+    /// expectations' lock in `__mockall_guard`.
+    fn call_default(&self, tbf: &Turbofish<'_>) -> TokenStream
+    {
+        let Some(default_impl) = &self.default_impl else {
+            return TokenStream::new();
+        };
+        let argnames = &self.argnames;
+        let predexprs = &self.predexprs;
+        let default_fn = gen_default_fn_ident(&self.sig.ident);
+        let expectations = if self.is_static {
+            quote!(__mockall_guard)
+        } else {
+            let name = self.name();
+            if let Some(trait_) = &self.trait_ {
+                let ident = format_ident!("{}_expectations", trait_);
+                quote!(self.#ident.#name)
+            } else {
+                quote!(self.#name)
+            }
+        };
+        let (self_arg, unlock) = if self.is_static {
+            // The default implementation may call other mock methods
+            (quote!(), quote!(drop(__mockall_guard);))
+        } else {
+            (quote!(self,), quote!())
+        };
+        quote!(
+            let __mockall_found = #expectations.find #tbf(#(#predexprs, )*);
+            if #expectations.call_default #tbf(&__mockall_found,
+                #(#predexprs, )*)
+            {
+                #unlock
+                return <Self as #default_impl>::#default_fn(
+                    #self_arg #(#argnames, )*
+                );
+            }
+        )
+    }
+
+    /// Generate the Expectation methods that choose the trait's default
+    /// implementation, if this method has a callable one.
+    ///
+    /// # Arguments
+    ///
+    /// * `locked`: Is the Expectation's Rfunc wrapped in a Mutex?
+    fn call_default_methods(&self, locked: bool) -> TokenStream {
+        if self.default_impl.is_none() {
+            return TokenStream::new();
+        }
+        let v = &self.privmod_vis;
+        let rfunc = if locked {
+            quote!(*self.rfunc.lock().unwrap())
+        } else {
+            quote!(self.rfunc)
+        };
+        quote!(
+            /// Call the trait's default implementation of this method,
+            /// instead of returning a value from the `Expectation`.
+            #v fn call_default(&mut self) -> &mut Self {
+                #rfunc = Rfunc::CallDefault;
+                self
+            }
+
+            /// Should this `Expectation` call the trait's default
+            /// implementation?
+            fn calls_default(&self) -> bool {
+                matches!(#rfunc, Rfunc::CallDefault)
+            }
+        )
+    }
+
+    /// Generate code for the calls_ method
+    pub fn calls_fn(&self) -> impl ToTokens {
+        let attrs = AttrFormatter::new(&self.attrs)
+            .doc(false)
+            .format();
+        let name = self.name();
+        let calls_ident = format_ident!("calls_{}", name);
+        let (_, tg, _) = if self.is_method_generic() {
+            &self.egenerics
+        } else {
+            &self.call_generics
+        }.split_for_impl();
+        let (ig, _, wc) = self.call_generics.split_for_impl();
+        let wc = self.calls_wc(wc, &self.call_recty);
+        let tbf = tg.as_turbofish();
+        let vis = &self.call_vis;
+        let call_recty = &self.call_recty;
+        let substruct_obj = if let Some(trait_) = &self.trait_ {
+            let ident = format_ident!("{trait_}_expectations");
+            quote!(#ident.)
+        } else {
+            quote!()
+        };
+        let docstr = format!("Return the arguments of every call to the `{}` method, in order.", self.sig.ident);
+        quote!(
+            #[doc = #docstr]
+            ///
+            /// Arguments that aren't `Clone + Send + 'static` can't be
+            /// recorded, and will always be `None`.
+            #(#attrs)*
+            #[allow(clippy::multiple_bound_locations)]
+            #[allow(clippy::type_complexity)]
+            #vis fn #calls_ident #ig(&self)
+                -> ::std::vec::Vec<(#(Option<#call_recty>,)*)>
+                #wc
+            {
+                self.#substruct_obj #name.calls #tbf()
+            }
+        )
+    }
+
+    /// Add the bounds required to read the call history to a where clause.
+    fn calls_wc(&self, wc: Option<&WhereClause>, recty: &[Type])
+        -> Option<WhereClause>
+    {
+        let mut wc = wc.cloned();
+        if !recty.is_empty() {
+            let wc = wc.get_or_insert_with(|| WhereClause {
+                where_token: <Token![where]>::default(),
+                predicates: Punctuated::new()
+            });
+            for ty in recty {
+                wc.predicates.push(parse2(quote!(#ty: 'static)).unwrap());
+            }
+        }
+        wc
+    }
+
+    /// Generate a code fragment that will record a call's arguments in the
+    /// Expectations object's call history
+    fn record(&self) -> impl ToTokens {
+        let recexprs = &self.recexprs;
+        quote!(
+            {
+                #[allow(unused_imports)]
+                use ::mockall::{ViaClone, ViaNoClone};
+                self.record((#(#recexprs,)*));
+            }
+        )
+    }
+
     /// Return this method's contribution to its parent's checkpoint method
     pub fn checkpoint(&self) -> impl ToTokens {
         let attrs = AttrFormatter::new(&self.attrs)
@@ -596,6 +916,16 @@ impl MockFunction {
         }
     }
 
+    /// Return a fragment of code that will make this method of
+    /// `__mockall_mock` lenient
+    pub fn lenient(&self) -> impl ToTokens {
+        let attrs = AttrFormatter::new(&self.attrs)
+            .doc(false)
+            .format();
+        let name = &self.name();
+        quote!(#(#attrs)* { __mockall_mock.#name.lenient(); })
+    }
+
     /// Return a function that creates a Context object for this function
     ///
     /// # Arguments
@@ -611,7 +941,13 @@ impl MockFunction {
             modname.map(|m| format!("{m}/")).unwrap_or_default(),
             self.inner_mod_ident(),
             self.name());
+        let exclusive_docstr = format!("Create a [`Context`]({}{}/struct.Context.html) for mocking the `{}` method, waiting until no other thread holds an exclusive one",
+            modname.map(|m| format!("{m}/")).unwrap_or_default(),
+            self.inner_mod_ident(),
+            self.name());
         let context_ident = format_ident!("{}_context", self.name());
+        let exclusive_ident = format_ident!("{}_context_exclusive",
+                                            self.name());
         let (_, tg, _) = self.type_generics.split_for_impl();
         let outer_mod_path = self.outer_mod_path(modname);
         let v = &self.call_vis;
@@ -622,6 +958,13 @@ impl MockFunction {
             {
                 #outer_mod_path::Context::default()
             }
+
+            #(#attrs)*
+            #[doc = #exclusive_docstr]
+            #v fn #exclusive_ident() -> #outer_mod_path::Context #tg
+            {
+                #outer_mod_path::Context::exclusive()
+            }
         )
     }
 
@@ -644,20 +987,56 @@ impl MockFunction {
     ///
     /// * `modname`:    Name of the parent struct's private module
     /// * `self_args`:  If supplied, these are the
-    ///                 AngleBracketedGenericArguments of the self type of the
-    ///                 trait impl.  e.g. The `T` in `impl Foo for Bar<T>`.
+    ///   AngleBracketedGenericArguments of the self type of the trait impl.
+    ///   e.g. The `T` in `impl Foo for Bar<T>`.
     // Supplying modname is an unfortunately hack.  Ideally MockFunction
     // wouldn't need to know that.
     pub fn expect(&self, modname: &Ident, self_args: Option<&PathArguments>)
         -> impl ToTokens
+    {
+        let docstr = format!("Create an [`Expectation`]({}/{}/struct.Expectation.html) for mocking the `{}` method",
+            modname, self.inner_mod_ident(), self.sig.ident);
+        self.expectation_accessor(modname, self_args, "expect",
+                                  quote!(#[doc = #docstr]))
+    }
+
+    /// Generate code for the fallback_ method
+    ///
+    /// # Arguments
+    ///
+    /// * `modname`:    Name of the parent struct's private module
+    /// * `self_args`:  If supplied, these are the
+    ///   AngleBracketedGenericArguments of the self type of the trait impl.
+    ///   e.g. The `T` in `impl Foo for Bar<T>`.
+    pub fn fallback(&self, modname: &Ident, self_args: Option<&PathArguments>)
+        -> impl ToTokens
+    {
+        let docstr = format!("Return the [`Expectation`]({}/{}/struct.Expectation.html) used for calls to the `{}` method that don't match any other.",
+            modname, self.inner_mod_ident(), self.sig.ident);
+        let doc = quote!(
+            #[doc = #docstr]
+            ///
+            /// The fallback is checked after every other expectation, and
+            /// it is discarded by `checkpoint`.
+        );
+        self.expectation_accessor(modname, self_args, "fallback", doc)
+    }
+
+    /// Generate a method that returns one of this function's Expectation
+    /// objects, like `expect_foo` or `fallback_foo`.
+    fn expectation_accessor(&self,
+                            modname: &Ident,
+                            self_args: Option<&PathArguments>,
+                            prefix: &str,
+                            doc: TokenStream) -> TokenStream
     {
         let attrs = AttrFormatter::new(&self.attrs)
             .doc(false)
             .format();
         let name = self.name();
-        let expect_ident = format_ident!("expect_{}", name);
+        let accessor_ident = format_ident!("{}_{}", prefix, name);
+        let inner_accessor = format_ident!("{}", prefix);
         let expectation_obj = self.expectation_obj(self_args);
-        let funcname = &self.sig.ident;
         let (_, tg, _) = if self.is_method_generic() {
             &self.egenerics
         } else {
@@ -686,17 +1065,16 @@ impl MockFunction {
         } else {
             quote!()
         };
-        let docstr = format!("Create an [`Expectation`]({}/{}/struct.Expectation.html) for mocking the `{}` method",
-            modname, self.inner_mod_ident(), funcname);
         quote!(
             #must_use
-            #[doc = #docstr]
+            #doc
             #(#attrs)*
-            #vis fn #expect_ident #ig(&mut self)
+            #[track_caller]
+            #vis fn #accessor_ident #ig(&mut self)
                -> &mut #modname::#expectation_obj
                #wc
             {
-                self.#substruct_obj #name.expect #tbf()
+                self.#substruct_obj #name.#inner_accessor #tbf()
             }
         )
     }
@@ -707,10 +1085,15 @@ impl MockFunction {
     {
         let inner_mod_ident = self.inner_mod_ident();
         if let Some(PathArguments::AngleBracketed(abga)) = self_args {
-            // staticize any lifetimes that might be present in the Expectation
-            // object but not in the self args.  These come from the method's
-            // return type.
+            // The Expectation never takes the struct's lifetime parameters.
+            // But staticize any lifetimes that might be present in the
+            // Expectation object but not in the self args.  These come from
+            // the method's return type.
             let mut abga2 = abga.clone();
+            abga2.args = abga.args.iter()
+                .filter(|ga| !matches!(ga, GenericArgument::Lifetime(_)))
+                .cloned()
+                .collect();
             for _ in self.egenerics.lifetimes() {
                 let lt = Lifetime::new("'static", Span::call_site());
                 let la = GenericArgument::Lifetime(lt);
@@ -782,15 +1165,31 @@ impl MockFunction {
 
     fn is_expectation_generic(&self) -> bool {
         self.egenerics.params.iter().any(|p| {
-            matches!(p, GenericParam::Type(_))
+            matches!(p, GenericParam::Type(_) | GenericParam::Const(_))
         }) || self.egenerics.where_clause.is_some()
     }
 
+    /// The type of a static method's reference to its expectations.
+    /// Thread-local expectations are reference counted, so they can be
+    /// dropped when their thread exits.
+    fn expectations_ref(&self) -> TokenStream {
+        let ty = if self.is_expectation_generic() {
+            quote!(GenericExpectations)
+        } else {
+            quote!(Expectations)
+        };
+        if self.thread_local {
+            quote!(::std::sync::Arc<::std::sync::Mutex<#ty>>)
+        } else {
+            quote!(&'static ::std::sync::Mutex<#ty>)
+        }
+    }
+
     /// Is the mock method generic (as opposed to a non-generic method of a
     /// generic mock struct)?
     pub fn is_method_generic(&self) -> bool {
         self.call_generics.params.iter().any(|p| {
-            matches!(p, GenericParam::Type(_))
+            matches!(p, GenericParam::Type(_) | GenericParam::Const(_))
         }) || self.call_generics.where_clause.is_some()
     }
 
@@ -808,6 +1207,33 @@ impl MockFunction {
         format_ident!("__{}", &self.name())
     }
 
+    /// Can a shared handle forward this method to the mock struct?
+    ///
+    /// Forwarding goes through a lock, so the method can't consume or pin
+    /// `self`, return anything borrowed, or be `async`.  And since `Self` was
+    /// replaced by the mock struct's name, that name may not appear at all.
+    pub fn is_shareable(&self) -> bool {
+        if self.sig.asyncness.is_some() {
+            return false;
+        }
+        if let Some(receiver) = self.sig.receiver() {
+            if receiver.reference.is_none() {
+                return false;
+            }
+        }
+        let mentions_struct = |ty: &Type| self.struct_.as_ref()
+            .is_some_and(|s| type_mentions(ty, s));
+        let args_ok = self.sig.inputs.iter().all(|input| match input {
+            FnArg::Typed(pt) => !mentions_struct(&pt.ty),
+            FnArg::Receiver(_) => true
+        });
+        let output_ok = match &self.sig.output {
+            ReturnType::Default => true,
+            ReturnType::Type(_, ty) => !(type_borrows(ty) || mentions_struct(ty))
+        };
+        args_ok && output_ok
+    }
+
     pub fn is_static(&self) -> bool {
         self.is_static
     }
@@ -816,12 +1242,48 @@ impl MockFunction {
         &self.sig.ident
     }
 
+    /// Generate a method for the shared handle that forwards to the mock
+    /// struct's implementation of a trait.
+    ///
+    /// # Arguments
+    ///
+    /// * `mock_ty`:    The mock struct's type, as the trait is implemented on
+    /// * `trait_path`: Path of the trait that this method belongs to
+    pub fn shared_call(&self, mock_ty: &PathSegment, trait_path: &Path)
+        -> impl ToTokens
+    {
+        let attrs = AttrFormatter::new(&self.attrs)
+            .must_use(true)
+            .format();
+        let argnames = &self.argnames;
+        let name = self.name();
+        let sig = &self.sig;
+        let receiver = match self.sig.receiver() {
+            None => quote!(),
+            Some(r) if r.mutability.is_some() => quote!(
+                &mut *self.0.lock()
+                    .unwrap_or_else(::std::sync::PoisonError::into_inner),
+            ),
+            Some(_) => quote!(
+                &*self.0.lock()
+                    .unwrap_or_else(::std::sync::PoisonError::into_inner),
+            )
+        };
+        quote!(
+            #(#attrs)*
+            #sig {
+                <#mock_ty as #trait_path>::#name(#receiver #(#argnames),*)
+            }
+        )
+    }
+
     /// Generate code for this function's private module
     pub fn priv_module(&self) -> impl ToTokens {
         let attrs = AttrFormatter::new(&self.attrs)
             .doc(false)
             .format();
         let common = &Common{f: self};
+        let const_params = gen_const_params(&self.egenerics);
         let context = &Context{f: self};
         let expectation: Box<dyn ToTokens> = if self.return_ref {
             Box::new(RefExpectation{f: self})
@@ -872,6 +1334,7 @@ impl MockFunction {
                     sync::Mutex,
                     vec::Vec,
                 };
+                #const_params
                 #rfunc
                 #matcher
                 #common
@@ -933,7 +1396,14 @@ impl<'a> ToTokens for Common<'a> {
             /// Holds the stuff that is independent of the output type
             struct Common #ig #wc {
                 matcher: Mutex<Matcher #tg>,
-                seq_handle: Option<::mockall::SeqHandle>,
+                /// Where the expectation was created, for failure messages
+                location: Option<&'static ::std::panic::Location<'static>>,
+                /// Label used in failure messages
+                name: Option<::std::string::String>,
+                /// Have too many calls already been reported to a failure
+                /// handler?  If so, don't report them again on drop.
+                overcalled: ::std::sync::atomic::AtomicBool,
+                seq_handles: ::mockall::SeqHandles,
                 times: ::mockall::Times
             }
 
@@ -942,7 +1412,10 @@ impl<'a> ToTokens for Common<'a> {
                 fn default() -> Self {
                     Common {
                         matcher: Mutex::new(Matcher::default()),
-                        seq_handle: None,
+                        location: None,
+                        name: None,
+                        overcalled: ::std::sync::atomic::AtomicBool::new(false),
+                        seq_handles: ::mockall::SeqHandles::default(),
                         times: ::mockall::Times::default()
                     }
                 }
@@ -952,30 +1425,95 @@ impl<'a> ToTokens for Common<'a> {
                 fn call(&self, desc: &str) {
                     self.times.call()
                         .unwrap_or_else(|m| {
-                            let desc = std::format!(
-                                "{}", self.matcher.lock().unwrap());
-                            panic!("{}: Expectation({}) {}", #funcname, desc,
-                                m);
+                            ::mockall::fail(::mockall::FailureKind::TooManyCalls,
+                                std::format!("{} {}{}", self.describe(), m,
+                                    self.location_suffix()));
+                            // The failure handler didn't panic, so it has
+                            // already seen this failure.
+                            self.overcalled.store(true,
+                                ::std::sync::atomic::Ordering::Relaxed);
                         });
-                    self.verify_sequence(desc);
+                    if let Some(__mockall_name) = &self.name {
+                        self.seq_handles.verify(
+                            &std::format!("{desc} named {__mockall_name:?}"),
+                            self.location);
+                    } else {
+                        self.seq_handles.verify(desc, self.location);
+                    }
                     if ::mockall::ExpectedCalls::TooFew != self.times.is_satisfied() {
-                        self.satisfy_sequence()
+                        self.seq_handles.satisfy()
+                    }
+                }
+
+                /// Require this expectation to be called only after the one
+                /// identified by `__mockall_pred`.
+                fn after(&mut self, __mockall_pred: &::mockall::ExpectationHandle)
+                {
+                    self.update_sequence();
+                    self.seq_handles.after(__mockall_pred, &self.describe());
+                }
+
+                /// Describe this expectation, for failure messages
+                fn describe(&self) -> ::std::string::String {
+                    let __mockall_matcher = self.matcher.lock().unwrap();
+                    if let Some(__mockall_name) = &self.name {
+                        std::format!("{}: Expectation({}) named {:?}",
+                            #funcname, __mockall_matcher, __mockall_name)
+                    } else {
+                        std::format!("{}: Expectation({})", #funcname,
+                            __mockall_matcher)
                     }
                 }
 
+                fn completion(&self) -> ::mockall::Completion {
+                    self.update_sequence();
+                    self.seq_handles.completion()
+                }
+
+                fn handle(&self) -> ::mockall::ExpectationHandle {
+                    self.update_sequence();
+                    self.seq_handles.handle(self.describe())
+                }
+
                 fn in_sequence(&mut self, __mockall_seq: &mut ::mockall::Sequence)
                     -> &mut Self
                 {
-                    assert!(self.times.is_exact(),
-                        "Only Expectations with an exact call count have sequences");
-                    self.seq_handle = Some(__mockall_seq.next_handle());
+                    self.update_sequence();
+                    self.seq_handles.push(__mockall_seq);
                     self
                 }
 
+                /// Explain why this expectation wasn't used for a call.
+                /// `matched` is whether its matcher accepted the arguments.
+                #[allow(clippy::ptr_arg)]
+                fn explain #lg (&self, #( #argnames: &#predty, )*
+                    matched: bool) -> ::std::string::String
+                {
+                    let __mockall_desc = self.describe();
+                    if matched {
+                        std::format!(
+                            "{} matches, but has already been called {} time(s)\n",
+                            __mockall_desc, self.times.count())
+                    } else {
+                        std::format!("{} does not match:\n{}", __mockall_desc,
+                            self.matcher.lock().unwrap()
+                                .explain(#(#argnames, )*))
+                    }
+                }
+
                 fn is_done(&self) -> bool {
                     self.times.is_done()
                 }
 
+                /// Describe where this expectation was created, if known, for
+                /// the end of failure messages.
+                fn location_suffix(&self) -> ::std::string::String {
+                    self.location
+                        .map(|__mockall_l|
+                             std::format!(" (expectation set at {__mockall_l})"))
+                        .unwrap_or_default()
+                }
+
                 #[allow(clippy::ptr_arg)]
                 fn matches #lg (&self, #( #argnames: &#predty, )*) -> bool {
                     self.matcher.lock().unwrap().matches(#(#argnames, )*)
@@ -984,12 +1522,7 @@ impl<'a> ToTokens for Common<'a> {
                 /// Forbid this expectation from ever being called.
                 fn never(&mut self) {
                     self.times.never();
-                }
-
-                fn satisfy_sequence(&self) {
-                    if let Some(__mockall_handle) = &self.seq_handle {
-                        __mockall_handle.satisfy()
-                    }
+                    self.update_sequence();
                 }
 
                 /// Expect this expectation to be called any number of times
@@ -997,7 +1530,16 @@ impl<'a> ToTokens for Common<'a> {
                 fn times<MockallR>(&mut self, __mockall_r: MockallR)
                     where MockallR: Into<::mockall::TimesRange>
                 {
-                    self.times.times(__mockall_r)
+                    self.times.times(__mockall_r);
+                    self.update_sequence();
+                }
+
+                /// Tell the sequences, if any, whether they may skip this
+                /// expectation.
+                fn update_sequence(&self) {
+                    self.seq_handles.set_optional(
+                        ::mockall::ExpectedCalls::TooFew !=
+                            self.times.is_satisfied())
                 }
 
                 #with_method
@@ -1021,33 +1563,32 @@ impl<'a> ToTokens for Common<'a> {
                              ::mockall::Fragile::new(Box::new(__mockall_f))
                         );
                 }
-
-                fn verify_sequence(&self, desc: &str) {
-                    if let Some(__mockall_handle) = &self.seq_handle {
-                        __mockall_handle.verify(desc)
-                    }
-                }
             }
 
             impl #ig Drop for Common #tg #wc {
                 fn drop(&mut self) {
                     if !::std::thread::panicking() {
-                        let desc = std::format!(
-                            "{}", self.matcher.lock().unwrap());
+                        let desc = self.describe();
                         match self.times.is_satisfied() {
                             ::mockall::ExpectedCalls::TooFew => {
-                                panic!("{}: Expectation({}) called {} time(s) which is fewer than expected {}",
-                                    #funcname,
-                                    desc,
-                                    self.times.count(),
-                                    self.times.minimum());
+                                ::mockall::fail(
+                                    ::mockall::FailureKind::TooFewCalls,
+                                    std::format!("{} called {} time(s) which is fewer than expected {}{}",
+                                        desc,
+                                        self.times.count(),
+                                        self.times.minimum(),
+                                        self.location_suffix()));
                             },
-                            ::mockall::ExpectedCalls::TooMany => {
-                                panic!("{}: Expectation({}) called {} time(s) which is more than expected {}",
-                                    #funcname,
-                                    desc,
-                                    self.times.count(),
-                                    self.times.maximum());
+                            ::mockall::ExpectedCalls::TooMany
+                                if !*self.overcalled.get_mut() =>
+                            {
+                                ::mockall::fail(
+                                    ::mockall::FailureKind::TooManyCalls,
+                                    std::format!("{} called {} time(s) which is more than expected {}{}",
+                                        desc,
+                                        self.times.count(),
+                                        self.times.maximum(),
+                                        self.location_suffix()));
                             },
                             _ => ()
                         }
@@ -1101,8 +1642,34 @@ impl<'a> ToTokens for CommonExpectationMethods<'a> {
             )
         };
         quote!(
+            /// Require this expectation to be called only after the one
+            /// identified by `__mockall_pred`.  May be used more than once.
+            ///
+            /// See [`ExpectationHandle`](../../../mockall/struct.ExpectationHandle.html).
+            #v fn after(&mut self,
+                __mockall_pred: &::mockall::ExpectationHandle) -> &mut Self
+            {
+                self.common.after(__mockall_pred);
+                self
+            }
+
+            /// Create a future that completes once this expectation has been
+            /// called at least as many times as it requires.
+            ///
+            /// See [`Completion`](../../../mockall/struct.Completion.html).
+            #v fn completion(&self) -> ::mockall::Completion {
+                self.common.completion()
+            }
+
+            /// Create a handle to this expectation, so that others can be
+            /// ordered after it with `after`.
+            #v fn handle(&self) -> ::mockall::ExpectationHandle {
+                self.common.handle()
+            }
+
             /// Add this expectation to a
-            /// [`Sequence`](../../../mockall/struct.Sequence.html).
+            /// [`Sequence`](../../../mockall/struct.Sequence.html).  May be
+            /// used more than once, to join several sequences.
             #v fn in_sequence(&mut self, __mockall_seq: &mut ::mockall::Sequence)
                 -> &mut Self
             {
@@ -1120,6 +1687,15 @@ impl<'a> ToTokens for CommonExpectationMethods<'a> {
                 self.common.matches(#(#argnames, )*)
             }
 
+            /// Label this expectation, to identify it in failure messages.
+            #v fn named<MockallS>(&mut self, __mockall_name: MockallS)
+                -> &mut Self
+                where MockallS: Into<::std::string::String>
+            {
+                self.common.name = Some(__mockall_name.into());
+                self
+            }
+
             /// Forbid this expectation from ever being called.
             #v fn never(&mut self) -> &mut Self {
                 self.common.never();
@@ -1191,31 +1767,212 @@ struct CommonExpectationsMethods<'a> {
 impl<'a> ToTokens for CommonExpectationsMethods<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
+        let argrecords = self.f.recty.iter()
+            .map(|_| quote!(::mockall::ArgRecord,))
+            .collect::<TokenStream>();
+        let calls_wc = self.f.calls_wc(None, &self.f.recty);
+        let indices = (0..self.f.recty.len())
+            .map(syn::Index::from)
+            .collect::<Vec<_>>();
+        let recty = &self.f.recty;
         let v = &self.f.privmod_vis;
+        let argnames = &self.f.argnames;
+        let lg = lifetimes_to_generics(&self.f.alifetimes);
+        let predty = &self.f.predty;
+        let call_default = if self.f.default_impl.is_some() {
+            let default_recexprs = &self.f.default_recexprs;
+            let desc = self.f.desc();
+            quote!(
+                /// Check whether the expectation that `find` chose for a call
+                /// should call the trait's default implementation.  If so,
+                /// record the call and return true.
+                #[allow(clippy::ptr_arg)]
+                #v fn call_default #lg (&self, __mockall_found: &Found,
+                    #(#argnames: &#predty, )*) -> bool
+                {
+                    use ::mockall::{ViaDebug, ViaNothing};
+                    let __mockall_e = match __mockall_found {
+                        ::std::result::Result::Ok(__mockall_i) =>
+                            self.found(*__mockall_i),
+                        ::std::result::Result::Err(_) => return false
+                    };
+                    if !__mockall_e.calls_default() {
+                        return false;
+                    }
+                    {
+                        #[allow(unused_imports)]
+                        use ::mockall::{ViaClone, ViaNoClone};
+                        self.record((#(#default_recexprs,)*));
+                    }
+                    __mockall_e.common.call(&#desc);
+                    true
+                }
+            )
+        } else {
+            TokenStream::new()
+        };
         quote!(
+            /// The expectation chosen for a call: an index into
+            /// `Expectations::expectations`, or `None` for the fallback.  Or
+            /// else an explanation of why there isn't one.
+            #v type Found = ::std::result::Result<Option<usize>,
+                                                  ::std::string::String>;
+
             /// A collection of [`Expectation`](struct.Expectations.html)
             /// objects.  Users will rarely if ever use this struct directly.
             #[doc(hidden)]
-            #v struct Expectations #ig ( Vec<Expectation #tg>) #wc;
+            #v struct Expectations #ig #wc {
+                expectations: Vec<Expectation #tg>,
+                /// The arguments of every call to this method, in order
+                calls: Mutex<Vec<(#argrecords)>>,
+                /// Used for calls that don't match any other expectation
+                fallback: Option<Expectation #tg>,
+                /// Should the fallback return a default value?
+                lenient: bool
+            }
 
             impl #ig Expectations #tg #wc {
+                /// Return the arguments of every call to this method, in
+                /// order.
+                #[allow(clippy::multiple_bound_locations)]
+                #[allow(clippy::type_complexity)]
+                #v fn calls(&self) -> Vec<(#(Option<#recty>,)*)> #calls_wc
+                {
+                    self.calls.lock().unwrap()
+                        .iter()
+                        .map(|__mockall_c|
+                             (#(__mockall_c.#indices.get::<#recty>(),)*)
+                        ).collect()
+                }
+
+                #call_default
+
                 /// Verify that all current expectations are satisfied and clear
-                /// them.
+                /// them, along with the call history and the fallback.
                 #v fn checkpoint(&mut self) -> std::vec::Drain<Expectation #tg>
                 {
-                    self.0.drain(..)
+                    self.calls.get_mut().unwrap().clear();
+                    self.fallback = if self.lenient {
+                        Some(Self::lenient_fallback())
+                    } else {
+                        None
+                    };
+                    self.expectations.drain(..)
                 }
 
                 /// Create a new expectation for this method.
+                #[track_caller]
                 #v fn expect(&mut self) -> &mut Expectation #tg
                 {
-                    self.0.push(Expectation::default());
-                    let __mockall_l = self.0.len();
-                    &mut self.0[__mockall_l - 1]
+                    let mut __mockall_e = Expectation::default();
+                    __mockall_e.common.location =
+                        Some(::std::panic::Location::caller());
+                    self.expectations.push(__mockall_e);
+                    let __mockall_l = self.expectations.len();
+                    &mut self.expectations[__mockall_l - 1]
+                }
+
+                /// Return the expectation used for calls that don't match any
+                /// other, creating it if necessary.
+                #[track_caller]
+                #v fn fallback(&mut self) -> &mut Expectation #tg
+                {
+                    let __mockall_e = self.fallback.get_or_insert_with(
+                        Expectation::default);
+                    __mockall_e.common.location =
+                        Some(::std::panic::Location::caller());
+                    __mockall_e
+                }
+
+                /// Return a default value for calls that don't match any other
+                /// expectation.
+                #v fn lenient(&mut self) {
+                    self.fallback = Some(Self::lenient_fallback());
+                    self.lenient = true;
                 }
 
                 #v const fn new() -> Self {
-                    Self(Vec::new())
+                    Self {
+                        expectations: Vec::new(),
+                        calls: Mutex::new(Vec::new()),
+                        fallback: None,
+                        lenient: false
+                    }
+                }
+
+                /// Find the expectation that should handle a call with these
+                /// arguments: the first one that matches and isn't
+                /// saturated, or else the fallback, returned as `None`.  If
+                /// there isn't one, explain why not.
+                ///
+                /// Each matcher is evaluated at most once.
+                #[allow(clippy::ptr_arg)]
+                #v fn find #lg (&self, #(#argnames: &#predty, )*) -> Found
+                {
+                    let __mockall_n = self.expectations.len();
+                    let mut __mockall_matched = Vec::new();
+                    for (__mockall_i, __mockall_e) in
+                        self.expectations.iter().enumerate()
+                    {
+                        let __mockall_m = __mockall_e.matches(#(#argnames, )*);
+                        if __mockall_m &&
+                            (!__mockall_e.is_done() || __mockall_n == 1)
+                        {
+                            return ::std::result::Result::Ok(Some(__mockall_i));
+                        }
+                        __mockall_matched.push(__mockall_m);
+                    }
+                    let __mockall_fallback = self.fallback.as_ref()
+                        .map(|__mockall_e|
+                             __mockall_e.matches(#(#argnames, )*));
+                    if __mockall_fallback == Some(true) {
+                        return ::std::result::Result::Ok(None);
+                    }
+                    ::std::result::Result::Err(self.no_match_report(
+                        #(#argnames, )* &__mockall_matched, __mockall_fallback))
+                }
+
+                /// Return the expectation chosen by `find`.
+                fn found(&self, __mockall_i: Option<usize>)
+                    -> &Expectation #tg
+                {
+                    match __mockall_i {
+                        Some(__mockall_i) => &self.expectations[__mockall_i],
+                        None => self.fallback.as_ref().unwrap()
+                    }
+                }
+
+                /// Explain why no expectation was used for a call with these
+                /// arguments, given whether each one's matcher accepted them.
+                #[allow(clippy::ptr_arg)]
+                fn no_match_report #lg (&self, #(#argnames: &#predty, )*
+                    matched: &[bool], fallback: Option<bool>)
+                    -> ::std::string::String
+                {
+                    let mut __mockall_s = ::std::string::String::new();
+                    for (__mockall_i, (__mockall_e, __mockall_m)) in
+                        self.expectations.iter().zip(matched).enumerate()
+                    {
+                        __mockall_s.push_str(&std::format!(
+                            "\nExpectation {}: {}", __mockall_i,
+                            __mockall_e.common
+                                .explain(#(#argnames, )* *__mockall_m)
+                                .trim_end()));
+                    }
+                    if let (Some(__mockall_e), Some(__mockall_m)) =
+                        (&self.fallback, fallback)
+                    {
+                        __mockall_s.push_str(&std::format!("\nFallback: {}",
+                            __mockall_e.common
+                                .explain(#(#argnames, )* __mockall_m)
+                                .trim_end()));
+                    }
+                    __mockall_s
+                }
+
+                /// Record a call's arguments in the call history.
+                fn record(&self, __mockall_c: (#argrecords)) {
+                    self.calls.lock().unwrap().push(__mockall_c);
                 }
             }
             impl #ig Default for Expectations #tg #wc
@@ -1269,6 +2026,17 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
             .map(|(argname, id)| quote!(#argname: #id, ))
             .collect::<TokenStream>();
         let v = &self.f.privmod_vis;
+        let call_default_method = if self.f.default_impl.is_some() {
+            quote!(
+                /// Just like
+                /// [`Expectation::call_default`](struct.Expectation.html#method.call_default)
+                #v fn call_default(&mut self) -> &mut Expectation #tg {
+                    #expectations.expectations[self.i].call_default()
+                }
+            )
+        } else {
+            quote!()
+        };
         let with_method = if self.f.concretize {
             quote!()
         } else {
@@ -1278,30 +2046,62 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 #v fn with<#with_generics> (&mut self, #with_args)
                     -> &mut Expectation #tg
                 {
-                    #expectations.0[self.i].with(#(#argnames, )*)
+                    #expectations.expectations[self.i].with(#(#argnames, )*)
                 }
             )
         };
         quote!(
+            #call_default_method
+
+            /// Just like
+            /// [`Expectation::after`](struct.Expectation.html#method.after)
+            #v fn after(&mut self,
+                __mockall_pred: &::mockall::ExpectationHandle)
+                -> &mut Expectation #tg
+            {
+                #expectations.expectations[self.i].after(__mockall_pred)
+            }
+
+            /// Just like
+            /// [`Expectation::completion`](struct.Expectation.html#method.completion)
+            #v fn completion(&mut self) -> ::mockall::Completion {
+                #expectations.expectations[self.i].completion()
+            }
+
+            /// Just like
+            /// [`Expectation::handle`](struct.Expectation.html#method.handle)
+            #v fn handle(&mut self) -> ::mockall::ExpectationHandle {
+                #expectations.expectations[self.i].handle()
+            }
+
             /// Just like
             /// [`Expectation::in_sequence`](struct.Expectation.html#method.in_sequence)
             #v fn in_sequence(&mut self,
                 __mockall_seq: &mut ::mockall::Sequence)
                 -> &mut Expectation #tg
             {
-                #expectations.0[self.i].in_sequence(__mockall_seq)
+                #expectations.expectations[self.i].in_sequence(__mockall_seq)
+            }
+
+            /// Just like
+            /// [`Expectation::named`](struct.Expectation.html#method.named)
+            #v fn named<MockallS>(&mut self, __mockall_name: MockallS)
+                -> &mut Expectation #tg
+                where MockallS: Into<::std::string::String>
+            {
+                #expectations.expectations[self.i].named(__mockall_name)
             }
 
             /// Just like
             /// [`Expectation::never`](struct.Expectation.html#method.never)
             #v fn never(&mut self) -> &mut Expectation #tg {
-                #expectations.0[self.i].never()
+                #expectations.expectations[self.i].never()
             }
 
             /// Just like
             /// [`Expectation::once`](struct.Expectation.html#method.once)
             #v fn once(&mut self) -> &mut Expectation #tg {
-                #expectations.0[self.i].once()
+                #expectations.expectations[self.i].once()
             }
 
             /// Just like
@@ -1311,7 +2111,7 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 -> &mut Expectation #tg
                 where MockallOutput: Clone + Into<#output> + Send + 'static
             {
-                #expectations.0[self.i].return_const(__mockall_c)
+                #expectations.expectations[self.i].return_const(__mockall_c)
             }
 
             /// Just like
@@ -1321,7 +2121,7 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 -> &mut Expectation #tg
                 where MockallOutput: Clone + Into<#output> + 'static
             {
-                #expectations.0[self.i].return_const_st(__mockall_c)
+                #expectations.expectations[self.i].return_const_st(__mockall_c)
             }
 
             /// Just like
@@ -1331,7 +2131,7 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 where MockallF: #hrtb FnMut(#(#argty, )*)
                     -> #output + Send + 'static
             {
-                #expectations.0[self.i].returning(__mockall_f)
+                #expectations.expectations[self.i].returning(__mockall_f)
             }
 
             /// Just like
@@ -1341,7 +2141,7 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 where MockallF: #hrtb FnOnce(#(#argty, )*)
                                 -> #output + Send + 'static
             {
-                #expectations.0[self.i].return_once(__mockall_f)
+                #expectations.expectations[self.i].return_once(__mockall_f)
             }
 
             /// Just like
@@ -1351,7 +2151,7 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 where MockallF: #hrtb FnOnce(#(#argty, )*)
                                 -> #output + 'static
             {
-                #expectations.0[self.i].return_once_st(__mockall_f)
+                #expectations.expectations[self.i].return_once_st(__mockall_f)
             }
 
 
@@ -1362,7 +2162,17 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 where MockallF: #hrtb FnMut(#(#argty, )*)
                                 -> #output + 'static
             {
-                #expectations.0[self.i].returning_st(__mockall_f)
+                #expectations.expectations[self.i].returning_st(__mockall_f)
+            }
+
+            /// Just like
+            /// [`Expectation::returning_values`](struct.Expectation.html#method.returning_values)
+            #v fn returning_values<MockallI>(&mut self, __mockall_i: MockallI)
+                -> &mut Expectation #tg
+                where MockallI: IntoIterator,
+                      MockallI::Item: Into<#output> + Send + 'static
+            {
+                #expectations.expectations[self.i].returning_values(__mockall_i)
             }
 
             /// Just like
@@ -1371,7 +2181,7 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 -> &mut Expectation #tg
                 where MockallR: Into<::mockall::TimesRange>
             {
-                #expectations.0[self.i].times(__mockall_r)
+                #expectations.expectations[self.i].times(__mockall_r)
             }
 
             #with_method
@@ -1383,7 +2193,7 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 where MockallF: #hrtb Fn(#(&#predty, )*)
                                 -> bool + Send + 'static
             {
-                #expectations.0[self.i].withf(__mockall_f)
+                #expectations.expectations[self.i].withf(__mockall_f)
             }
 
             /// Just like
@@ -1393,7 +2203,7 @@ impl<'a> ToTokens for ExpectationGuardCommonMethods<'a> {
                 where MockallF: #hrtb Fn(#(&#predty, )*)
                                 -> bool + 'static
             {
-                #expectations.0[self.i].withf_st(__mockall_f)
+                #expectations.expectations[self.i].withf_st(__mockall_f)
             }
         ).to_tokens(tokens);
     }
@@ -1422,13 +2232,28 @@ impl<'a> ToTokens for ConcreteExpectationGuard<'a> {
         let (e_ig, e_tg, e_wc) = e_generics.split_for_impl();
         let (ei_ig, _, _) = e_generics.split_for_impl();
         let v = &self.f.privmod_vis;
-        quote!(
-            #[doc(hidden)]
-            #v fn get_expectations() -> &'static ::std::sync::Mutex<Expectations> {
+        let expectations_ref = self.f.expectations_ref();
+        let get_expectations_body = if self.f.thread_local {
+            quote!(
+                ::std::thread_local! {
+                    static EXPECTATIONS: #expectations_ref =
+                        ::std::sync::Arc::new(
+                            ::std::sync::Mutex::new(Expectations::new()));
+                }
+                EXPECTATIONS.with(|e| e.clone())
+            )
+        } else {
+            quote!(
                 static EXPECTATIONS:
                     ::std::sync::Mutex<Expectations #tg> =
                     ::std::sync::Mutex::new(Expectations::new());
                 &EXPECTATIONS
+            )
+        };
+        quote!(
+            #[doc(hidden)]
+            #v fn get_expectations() -> #expectations_ref {
+                #get_expectations_body
             }
 
             /// Like an [`&Expectation`](struct.Expectation.html) but
@@ -1454,11 +2279,12 @@ impl<'a> ToTokens for ConcreteExpectationGuard<'a> {
                 // Should only be called from the mockall_derive generated
                 // code
                 #[doc(hidden)]
+                #[track_caller]
                 #v fn new(mut __mockall_guard: MutexGuard<'__mockall_lt, Expectations #tg>)
                     -> Self
                 {
                     __mockall_guard.expect(); // Drop the &Expectation
-                    let __mockall_i = __mockall_guard.0.len() - 1;
+                    let __mockall_i = __mockall_guard.expectations.len() - 1;
                     ExpectationGuard{guard: __mockall_guard, i: __mockall_i}
                 }
 
@@ -1493,11 +2319,26 @@ impl<'a> ToTokens for GenericExpectationGuard<'a> {
         let fn_params = &self.f.fn_params;
         let tbf = tg.as_turbofish();
         let v = &self.f.privmod_vis;
-        quote!(
-            #[doc(hidden)]
-            #v fn get_expectations() -> &'static ::std::sync::Mutex<GenericExpectations> {
+        let expectations_ref = self.f.expectations_ref();
+        let get_expectations_body = if self.f.thread_local {
+            quote!(
+                ::std::thread_local! {
+                    static EXPECTATIONS: #expectations_ref =
+                        ::std::sync::Arc::new(::std::sync::Mutex::new(
+                            GenericExpectations::new()));
+                }
+                EXPECTATIONS.with(|e| e.clone())
+            )
+        } else {
+            quote!(
                 static CELL: ::std::sync::OnceLock<::std::sync::Mutex<GenericExpectations>> = ::std::sync::OnceLock::new();
                 CELL.get_or_init(|| ::std::sync::Mutex::new(GenericExpectations::new()))
+            )
+        };
+        quote!(
+            #[doc(hidden)]
+            #v fn get_expectations() -> #expectations_ref {
+                #get_expectations_body
             }
 
             /// Like an [`&Expectation`](struct.Expectation.html) but
@@ -1515,6 +2356,7 @@ impl<'a> ToTokens for GenericExpectationGuard<'a> {
                 // Should only be called from the mockall_derive generated
                 // code
                 #[doc(hidden)]
+                #[track_caller]
                 #v fn new(mut __mockall_guard: MutexGuard<'__mockall_lt, GenericExpectations>)
                     -> Self
                 {
@@ -1526,7 +2368,7 @@ impl<'a> ToTokens for GenericExpectationGuard<'a> {
                         .downcast_mut()
                         .unwrap();
                     __mockall_ee.expect();    // Drop the &Expectation
-                    let __mockall_i = __mockall_ee.0.len() - 1;
+                    let __mockall_i = __mockall_ee.expectations.len() - 1;
                     ExpectationGuard{guard: __mockall_guard, i: __mockall_i,
                         _phantom: ::std::marker::PhantomData}
                 }
@@ -1564,6 +2406,16 @@ impl<'a> ToTokens for Context<'a> {
         );
         meth_generics.params.push(GenericParam::Lifetime(ltdef));
         let (meth_ig, _meth_tg, meth_wc) = meth_generics.split_for_impl();
+        let (calls_ig, _, calls_wc) = self.f.call_generics.split_for_impl();
+        let recty = &self.f.recty;
+        let calls_wc = self.f.calls_wc(calls_wc, recty);
+        let (_, tg, _) = self.f.egenerics.split_for_impl();
+        let calls_tbf = if self.f.is_expectation_generic() {
+            let tbf = tg.as_turbofish();
+            quote!(#tbf)
+        } else {
+            quote!()
+        };
         let ctx_fn_params = self.f.struct_generics.type_params()
             .map(|tp| tp.ident.clone())
             .collect::<Punctuated::<Ident, Token![,]>>();
@@ -1581,18 +2433,56 @@ impl<'a> ToTokens for Context<'a> {
         #[cfg(feature = "nightly_derive")]
         let clear_poison = quote!(
             #[allow(clippy::incompatible_msrv)]
-            get_expectations().clear_poison();
+            self.expectations.clear_poison();
         );
+        let sync_doc = if self.f.thread_local {
+            "Expectations are stored per thread, so each test thread sees \
+            only its own.  A `Context` always refers to the expectations of \
+            the thread that created it."
+        } else {
+            "A `Context` created with [`exclusive`](#method.exclusive) also \
+            serializes tests.  While one exists, creating another exclusive \
+            `Context` for the same method on any other thread will block \
+            until it drops."
+        };
+        let expectations_ref = self.f.expectations_ref();
+        // Thread-local expectations need no serialization, so they have no
+        // lock.  The ExpectationGuard borrows them from the Context.
+        let (get_context_lock, exclusive_lock, expect_self) =
+            if self.f.thread_local
+        {
+            (quote!(), quote!(None), quote!(&'__mockall_lt self))
+        } else {
+            (
+                quote!(
+                    #[doc(hidden)]
+                    #v fn get_context_lock() -> &'static ::mockall::ContextLock
+                    {
+                        static CONTEXT_LOCK: ::mockall::ContextLock =
+                            ::mockall::ContextLock::new();
+                        &CONTEXT_LOCK
+                    }
+                ),
+                quote!(Some(get_context_lock().lock())),
+                quote!(&self)
+            )
+        };
 
         quote!(
+            #get_context_lock
+
             /// Manages the context for expectations of static methods.
             ///
             /// Expectations on this method will be validated and cleared when
-            /// the `Context` object drops.  The `Context` object does *not*
-            /// provide any form of synchronization, so multiple tests that set
-            /// expectations on the same static method must provide their own.
+            /// the `Context` object drops.
+            ///
+            #[doc = #sync_doc]
             #[must_use = "Context only serves to create expectations" ]
             #v struct Context #ty_ig #ty_wc {
+                /// Held by exclusive `Context`s
+                _lock: Option<::mockall::ContextLockGuard>,
+                /// The expectations of the thread that created this `Context`
+                expectations: #expectations_ref,
                 // Prevent "unused type parameter" errors
                 // Surprisingly, PhantomData<Fn(generics)> is Send even if
                 // generics are not, unlike PhantomData<generics>
@@ -1601,14 +2491,39 @@ impl<'a> ToTokens for Context<'a> {
                 >
             }
             impl #ty_ig Context #ty_tg #ty_wc {
+                /// Create a `Context` that holds this method's lock for as
+                /// long as it exists.  Any other thread that tries to create
+                /// an exclusive `Context` for the same method will block until
+                /// this one, and any others on this thread, have dropped.
+                #v fn exclusive() -> Self {
+                    Context {
+                        _lock: #exclusive_lock,
+                        expectations: get_expectations(),
+                        _phantom: std::marker::PhantomData
+                    }
+                }
+
+                /// Return the arguments of every call to this method, in
+                /// order.
+                ///
+                /// Arguments that aren't `Clone + Send + 'static` can't be
+                /// recorded, and will always be `None`.
+                #[allow(clippy::multiple_bound_locations)]
+                #[allow(clippy::type_complexity)]
+                #v fn calls #calls_ig (&self) -> Vec<(#(Option<#recty>,)*)>
+                    #calls_wc
+                {
+                    self.expectations.lock().unwrap().calls #calls_tbf ()
+                }
+
                 /// Verify that all current expectations for this method are
-                /// satisfied and clear them.
+                /// satisfied and clear them, along with the call history.
                 #v fn checkpoint(&self) {
-                    Self::do_checkpoint()
+                    self.do_checkpoint()
                 }
                 #[doc(hidden)]
-                #v fn do_checkpoint() {
-                    let __mockall_timeses = get_expectations()
+                #v fn do_checkpoint(&self) {
+                    let __mockall_timeses = self.expectations
                         .lock()
                         .unwrap()
                         .checkpoint()
@@ -1617,15 +2532,20 @@ impl<'a> ToTokens for Context<'a> {
 
                 /// Create a new expectation for this method.
                 #must_use
-                #v fn expect #meth_ig ( &self,) -> ExpectationGuard #e_tg
+                #[track_caller]
+                #v fn expect #meth_ig (#expect_self) -> ExpectationGuard #e_tg
                     #meth_wc
                 {
-                    ExpectationGuard::new(get_expectations().lock().unwrap())
+                    ExpectationGuard::new(self.expectations.lock().unwrap())
                 }
             }
             impl #ty_ig Default for Context #ty_tg #ty_wc {
                 fn default() -> Self {
-                    Context {_phantom: std::marker::PhantomData}
+                    Context {
+                        _lock: None,
+                        expectations: get_expectations(),
+                        _phantom: std::marker::PhantomData
+                    }
                 }
             }
             impl #ty_ig Drop for Context #ty_tg #ty_wc {
@@ -1637,12 +2557,12 @@ impl<'a> ToTokens for Context<'a> {
                         // Drain all expectations so other tests can run with a
                         // blank slate.  But ignore errors so we don't
                         // double-panic.
-                        let _ = get_expectations()
+                        let _ = self.expectations
                             .lock()
                             .map(|mut g| g.checkpoint().collect::<Vec<_>>());
                     } else {
                         // Verify expectations are satisfied
-                        Self::do_checkpoint();
+                        self.do_checkpoint();
                     }
                 }
             }
@@ -1693,6 +2613,34 @@ impl<'a> ToTokens for Matcher<'a> {
         } else {
             quote!(Matcher::Pred(__mockall_pred) => [#pred_matches].iter().all(|__mockall_x| *__mockall_x),)
         };
+        let predexplain_body = if self.f.concretize {
+            quote!()
+        } else {
+            let explanations = argnames.iter().enumerate()
+                .map(|(i, argname)| {
+                    let idx = syn::Index::from(i);
+                    let name = argname.to_token_stream().to_string();
+                    quote!(
+                        match __mockall_pred.#idx.find_case(false, #argname) {
+                            Some(__mockall_case) => {
+                                let _ = writeln!(__mockall_s, "    {}: failed",
+                                    #name);
+                                let __mockall_tree = std::format!("{}",
+                                    ::mockall::CaseTreeExt::tree(&__mockall_case));
+                                for __mockall_line in __mockall_tree.lines() {
+                                    let _ = writeln!(__mockall_s, "        {}",
+                                        __mockall_line);
+                                }
+                            },
+                            None => {
+                                let _ = writeln!(__mockall_s, "    {}: passed",
+                                    #name);
+                            }
+                        }
+                    )
+                }).collect::<TokenStream>();
+            quote!(Matcher::Pred(__mockall_pred) => { #explanations },)
+        };
         let preddbg_body = if self.f.concretize {
             quote!()
         } else {
@@ -1728,6 +2676,31 @@ impl<'a> ToTokens for Matcher<'a> {
                         _ => unreachable!()
                     }
                 }
+
+                /// Explain why these arguments, which `matches` already
+                /// rejected, don't match.
+                ///
+                /// Functions aren't called again.  Predicates are evaluated
+                /// again with `find_case`, so those with side effects, like
+                /// `Captor`, can tell the difference.
+                #[allow(clippy::ptr_arg)]
+                #[allow(unused_variables)]
+                fn explain #lg (&self, #( #argnames: &#predty, )*)
+                    -> ::std::string::String
+                {
+                    use ::std::fmt::Write;
+                    let mut __mockall_s = ::std::string::String::new();
+                    match self {
+                        Matcher::Always => (),
+                        Matcher::Func(_) | Matcher::FuncSt(_) => {
+                            let _ = writeln!(__mockall_s,
+                                "    {} returned false", self);
+                        },
+                        #predexplain_body
+                        _ => unreachable!()
+                    }
+                    __mockall_s
+                }
             }
 
             impl #ig Default for Matcher #tg #wc {
@@ -1772,10 +2745,19 @@ impl<'a> ToTokens for RefRfunc<'a> {
         let default_err_msg =
             "Can only return default values for types that impl std::Default";
 
+        let (cd_variant, cd_arm) = if self.f.default_impl.is_some() {
+            (quote!(CallDefault,),
+             quote!(Rfunc::CallDefault => unreachable!(),))
+        } else {
+            (quote!(), quote!())
+        };
         quote!(
             enum Rfunc #ig #wc {
                 Default(Option<#owned_output>),
                 Const(#owned_output),
+                // Call the trait's default implementation instead.  The mock
+                // method does that before ever calling the Rfunc.
+                #cd_variant
                 // Prevent "unused type parameter" errors Surprisingly,
                 // PhantomData<Fn(generics)> is Send even if generics are not,
                 // unlike PhantomData<generics>
@@ -1796,6 +2778,7 @@ impl<'a> ToTokens for RefRfunc<'a> {
                         Rfunc::Const(ref __mockall_o) => {
                             ::std::result::Result::Ok(__mockall_o)
                         },
+                        #cd_arm
                         Rfunc::_Phantom(_) => unreachable!()
                     }
                 }
@@ -1834,6 +2817,12 @@ impl<'a> ToTokens for RefMutRfunc<'a> {
         let default_err_msg =
             "Can only return default values for types that impl std::Default";
 
+        let (cd_variant, cd_arm) = if self.f.default_impl.is_some() {
+            (quote!(CallDefault,),
+             quote!(Rfunc::CallDefault => unreachable!(),))
+        } else {
+            (quote!(), quote!())
+        };
         quote!(
             #[allow(clippy::unused_unit)]
             enum Rfunc #ig #wc {
@@ -1846,6 +2835,9 @@ impl<'a> ToTokens for RefMutRfunc<'a> {
                        ), Option<#owned_output>
                 ),
                 Var(#owned_output),
+                // Call the trait's default implementation instead.  The mock
+                // method does that before ever calling the Rfunc.
+                #cd_variant
                 // Prevent "unused type parameter" errors Surprisingly,
                 // PhantomData<Fn(generics)> is Send even if generics are not,
                 // unlike PhantomData<generics>
@@ -1886,6 +2878,7 @@ impl<'a> ToTokens for RefMutRfunc<'a> {
                         Rfunc::Var(ref mut __mockall_o) => {
                             ::std::result::Result::Ok(__mockall_o)
                         },
+                        #cd_arm
                         Rfunc::_Phantom(_) => unreachable!()
                     }
                 }
@@ -1916,6 +2909,12 @@ impl<'a> ToTokens for StaticRfunc<'a> {
         let hrtb = self.f.hrtb();
         let lg = lifetimes_to_generics(&self.f.alifetimes);
         let output = &self.f.output;
+        let (cd_variant, cd_arm) = if self.f.default_impl.is_some() {
+            (quote!(CallDefault,),
+             quote!(Rfunc::CallDefault => unreachable!(),))
+        } else {
+            (quote!(), quote!())
+        };
         quote!(
             #[allow(clippy::unused_unit)]
             enum Rfunc #ig #wc {
@@ -1933,6 +2932,9 @@ impl<'a> ToTokens for StaticRfunc<'a> {
                 OnceSt(::mockall::Fragile<
                     Box<dyn #hrtb FnOnce(#(#argty, )*) -> #output>>
                 ),
+                // Call the trait's default implementation instead.  The mock
+                // method does that before ever calling the Rfunc.
+                #cd_variant
                 // Prevent "unused type parameter" errors Surprisingly,
                 // PhantomData<Fn(generics)> is Send even if generics are not,
                 // unlike PhantomData<generics>
@@ -1974,6 +2976,7 @@ impl<'a> ToTokens for StaticRfunc<'a> {
                                 unreachable!()
                             }
                         },
+                        #cd_arm
                         Rfunc::_Phantom(_) => unreachable!()
                     }
                 }
@@ -1999,8 +3002,8 @@ impl<'a> ToTokens for RefExpectation<'a> {
         let argnames = &self.f.argnames;
         let argty = &self.f.argty;
         let common_methods = CommonExpectationMethods{f: self.f};
+        let call_default_methods = self.f.call_default_methods(false);
         let desc = self.f.desc();
-        let funcname = self.f.funcname();
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
 
         let (_, common_tg, _) = self.f.cgenerics.split_for_impl();
@@ -2025,10 +3028,10 @@ impl<'a> ToTokens for RefExpectation<'a> {
                     use ::mockall::{ViaDebug, ViaNothing};
                     self.common.call(&#desc);
                     self.rfunc.call().unwrap_or_else(|m| {
-                        let desc = std::format!(
-                            "{}", self.common.matcher.lock().unwrap());
-                        panic!("{}: Expectation({}) {}", #funcname, desc,
-                            m);
+                        ::mockall::fail_fatal(
+                            ::mockall::FailureKind::MissingReturnValue,
+                            std::format!("{} {}{}", self.common.describe(), m,
+                                self.common.location_suffix()))
                     })
                 }
 
@@ -2040,6 +3043,8 @@ impl<'a> ToTokens for RefExpectation<'a> {
                     self
                 }
 
+                #call_default_methods
+
                 #common_methods
             }
             impl #ig Default for Expectation #tg #wc
@@ -2063,10 +3068,10 @@ struct RefMutExpectation<'a> {
 impl<'a> ToTokens for RefMutExpectation<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let common_methods = CommonExpectationMethods{f: self.f};
+        let call_default_methods = self.f.call_default_methods(false);
         let argnames = &self.f.argnames;
         let argty = &self.f.argty;
         let desc = self.f.desc();
-        let funcname = self.f.funcname();
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
         let (_, common_tg, _) = self.f.cgenerics.split_for_impl();
         let lg = lifetimes_to_generics(&self.f.alifetimes);
@@ -2089,11 +3094,12 @@ impl<'a> ToTokens for RefMutExpectation<'a> {
                 {
                     use ::mockall::{ViaDebug, ViaNothing};
                     self.common.call(&#desc);
-                    let desc = std::format!(
-                        "{}", self.common.matcher.lock().unwrap());
+                    let desc = self.common.describe();
+                    let __mockall_at = self.common.location_suffix();
                     self.rfunc.call_mut(#(#argnames, )*).unwrap_or_else(|m| {
-                            panic!("{}: Expectation({}) {}", #funcname, desc,
-                                   m);
+                            ::mockall::fail_fatal(
+                                ::mockall::FailureKind::MissingReturnValue,
+                                std::format!("{} {}{}", desc, m, __mockall_at))
                     })
                 }
 
@@ -2128,6 +3134,8 @@ impl<'a> ToTokens for RefMutExpectation<'a> {
                     self
                 }
 
+                #call_default_methods
+
                 #common_methods
             }
             impl #ig Default for Expectation #tg #wc
@@ -2151,11 +3159,12 @@ struct StaticExpectation<'a> {
 impl<'a> ToTokens for StaticExpectation<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let common_methods = CommonExpectationMethods{f: self.f};
+        let call_default_methods = self.f.call_default_methods(true);
         let argnames = &self.f.argnames;
         let argty = &self.f.argty;
         let desc = self.f.desc();
-        let hrtb = self.f.hrtb();
         let funcname = self.f.funcname();
+        let hrtb = self.f.hrtb();
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
         let (_, common_tg, _) = self.f.cgenerics.split_for_impl();
         let lg = lifetimes_to_generics(&self.f.alifetimes);
@@ -2180,10 +3189,10 @@ impl<'a> ToTokens for StaticExpectation<'a> {
                     self.common.call(&#desc);
                     self.rfunc.lock().unwrap().call_mut(#(#argnames, )*)
                         .unwrap_or_else(|message| {
-                            let desc = std::format!(
-                                "{}", self.common.matcher.lock().unwrap());
-                            panic!("{}: Expectation({}) {}", #funcname, desc,
-                                   message);
+                            ::mockall::fail_fatal(
+                                ::mockall::FailureKind::MissingReturnValue,
+                                std::format!("{} {}{}", self.common.describe(),
+                                    message, self.common.location_suffix()))
                         })
                 }
 
@@ -2302,6 +3311,38 @@ impl<'a> ToTokens for StaticExpectation<'a> {
                     self
                 }
 
+                /// Return each of the supplied values in turn, one per call.
+                ///
+                /// The expectation will be called exactly as many times as
+                /// there are values.  Use [`times`](#method.times) afterwards
+                /// to allow fewer calls.
+                ///
+                /// As with [`return_const`](#method.return_const), you will
+                /// usually need to specify the values' type explicitly.  i.e.
+                /// `returning_values([1i32, 2])` instead of
+                /// `returning_values([1, 2])`.
+                #[allow(unused_variables)]
+                #v fn returning_values<MockallI>(&mut self,
+                    __mockall_i: MockallI)
+                    -> &mut Self
+                    where MockallI: IntoIterator,
+                          MockallI::Item: Into<#output> + Send + 'static
+                {
+                    let mut __mockall_values = __mockall_i.into_iter()
+                        .collect::<::std::collections::VecDeque<_>>();
+                    self.times(__mockall_values.len());
+                    self.returning(move |#(#argnames, )*|
+                        __mockall_values.pop_front()
+                            .unwrap_or_else(|| ::mockall::fail_fatal(
+                                ::mockall::FailureKind::MissingReturnValue,
+                                std::format!("{}: returning_values ran out of values",
+                                    #funcname)))
+                            .into()
+                    )
+                }
+
+                #call_default_methods
+
                 #common_methods
             }
             impl #ig Default for Expectation #tg #wc
@@ -2330,7 +3371,9 @@ impl<'a> ToTokens for RefExpectations<'a> {
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
         let lg = lifetimes_to_generics(&self.f.alifetimes);
         let output = &self.f.output;
+        let owned_output = &self.f.owned_output;
         let predexprs = &self.f.predexprs;
+        let record = self.f.record();
         let v = &self.f.privmod_vis;
         quote!(
             #common_methods
@@ -2339,15 +3382,35 @@ impl<'a> ToTokens for RefExpectations<'a> {
                 /// will be checked in FIFO order and the first one with
                 /// matching arguments will be used.
                 #v fn call #lg (&self, #(#argnames: #argty, )* )
-                    -> Option<#output>
+                    -> ::std::result::Result<#output, ::std::string::String>
+                {
+                    let __mockall_found = self.find(#(#predexprs, )*);
+                    self.call_found(__mockall_found, #(#argnames, )*)
+                }
+
+                /// Call the expectation that `find` chose, or return its
+                /// explanation of why there isn't one.
+                #v fn call_found #lg (&self, __mockall_found: Found,
+                    #(#argnames: #argty, )* )
+                    -> ::std::result::Result<#output, ::std::string::String>
                 {
-                    self.0.iter()
-                        .find(|__mockall_e|
-                              __mockall_e.matches(#(#predexprs, )*) &&
-                              (!__mockall_e.is_done() || self.0.len() == 1))
-                        .map(move |__mockall_e|
-                             __mockall_e.call(#(#argnames),*)
-                        )
+                    #record
+                    let __mockall_e = self.found(__mockall_found?);
+                    ::std::result::Result::Ok(
+                        __mockall_e.call(#(#argnames, )*))
+                }
+
+                /// Create an expectation that returns a default value.
+                fn lenient_fallback() -> Expectation #tg {
+                    use ::mockall::{ViaDefault, ViaNoDefault};
+                    let mut __mockall_e = Expectation::default();
+                    if let ::std::result::Result::Ok(__mockall_o) =
+                        (&&::mockall::DefaultMaker::<#owned_output>::new())
+                        .make_default()
+                    {
+                        __mockall_e.return_const(__mockall_o);
+                    }
+                    __mockall_e
                 }
 
             }
@@ -2368,7 +3431,9 @@ impl<'a> ToTokens for RefMutExpectations<'a> {
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
         let lg = lifetimes_to_generics(&self.f.alifetimes);
         let output = &self.f.output;
+        let owned_output = &self.f.owned_output;
         let predexprs = &self.f.predexprs;
+        let record = self.f.record();
         let v = &self.f.privmod_vis;
         quote!(
             #common_methods
@@ -2377,16 +3442,40 @@ impl<'a> ToTokens for RefMutExpectations<'a> {
                 /// will be checked in FIFO order and the first one with
                 /// matching arguments will be used.
                 #v fn call_mut #lg (&mut self, #(#argnames: #argty, )* )
-                    -> Option<#output>
+                    -> ::std::result::Result<#output, ::std::string::String>
                 {
-                    let __mockall_n = self.0.len();
-                    self.0.iter_mut()
-                        .find(|__mockall_e|
-                              __mockall_e.matches(#(#predexprs, )*) &&
-                              (!__mockall_e.is_done() || __mockall_n == 1))
-                        .map(move |__mockall_e|
-                             __mockall_e.call_mut(#(#argnames, )*)
-                        )
+                    let __mockall_found = self.find(#(#predexprs, )*);
+                    self.call_mut_found(__mockall_found, #(#argnames, )*)
+                }
+
+                /// Call the expectation that `find` chose, or return its
+                /// explanation of why there isn't one.
+                #v fn call_mut_found #lg (&mut self, __mockall_found: Found,
+                    #(#argnames: #argty, )* )
+                    -> ::std::result::Result<#output, ::std::string::String>
+                {
+                    #record
+                    let __mockall_i = __mockall_found?;
+                    let __mockall_e = match __mockall_i {
+                        Some(__mockall_i) =>
+                            &mut self.expectations[__mockall_i],
+                        None => self.fallback.as_mut().unwrap()
+                    };
+                    ::std::result::Result::Ok(
+                        __mockall_e.call_mut(#(#argnames, )*))
+                }
+
+                /// Create an expectation that returns a default value.
+                fn lenient_fallback() -> Expectation #tg {
+                    use ::mockall::{ViaDefault, ViaNoDefault};
+                    let mut __mockall_e = Expectation::default();
+                    if let ::std::result::Result::Ok(__mockall_o) =
+                        (&&::mockall::DefaultMaker::<#owned_output>::new())
+                        .make_default()
+                    {
+                        __mockall_e.return_var(__mockall_o);
+                    }
+                    __mockall_e
                 }
 
             }
@@ -2407,7 +3496,9 @@ impl<'a> ToTokens for StaticExpectations<'a> {
         let (ig, tg, wc) = self.f.egenerics.split_for_impl();
         let lg = lifetimes_to_generics(&self.f.alifetimes);
         let output = &self.f.output;
+        let funcname = self.f.funcname();
         let predexprs = &self.f.predexprs;
+        let record = self.f.record();
         let v = &self.f.privmod_vis;
         quote!(
             #common_methods
@@ -2416,15 +3507,37 @@ impl<'a> ToTokens for StaticExpectations<'a> {
                 /// will be checked in FIFO order and the first one with
                 /// matching arguments will be used.
                 #v fn call #lg (&self, #(#argnames: #argty, )* )
-                    -> Option<#output>
+                    -> ::std::result::Result<#output, ::std::string::String>
+                {
+                    let __mockall_found = self.find(#(#predexprs, )*);
+                    self.call_found(__mockall_found, #(#argnames, )*)
+                }
+
+                /// Call the expectation that `find` chose, or return its
+                /// explanation of why there isn't one.
+                #v fn call_found #lg (&self, __mockall_found: Found,
+                    #(#argnames: #argty, )* )
+                    -> ::std::result::Result<#output, ::std::string::String>
                 {
-                    self.0.iter()
-                        .find(|__mockall_e|
-                              __mockall_e.matches(#(#predexprs, )*) &&
-                              (!__mockall_e.is_done() || self.0.len() == 1))
-                        .map(move |__mockall_e|
-                             __mockall_e.call(#(#argnames, )*)
-                        )
+                    #record
+                    let __mockall_e = self.found(__mockall_found?);
+                    ::std::result::Result::Ok(
+                        __mockall_e.call(#(#argnames, )*))
+                }
+
+                /// Create an expectation that returns a default value.
+                #[allow(unused_variables)]
+                fn lenient_fallback() -> Expectation #tg {
+                    let mut __mockall_e = Expectation::default();
+                    __mockall_e.returning(|#(#argnames, )*| {
+                        use ::mockall::{ViaDefault, ViaNoDefault};
+                        (&&::mockall::DefaultMaker::<#output>::new())
+                            .make_default()
+                            .unwrap_or_else(|m| ::mockall::fail_fatal(
+                                ::mockall::FailureKind::MissingReturnValue,
+                                std::format!("{}: {}", #funcname, m)))
+                    });
+                    __mockall_e
                 }
 
             }
@@ -2455,9 +3568,17 @@ impl<'a> ToTokens for GenericExpectations<'a> {
             #[derive(Default)]
             #v struct GenericExpectations{
                 store: std::collections::hash_map::HashMap<::mockall::Key,
-                               Box<dyn ::mockall::AnyExpectations>>
+                               Box<dyn ::mockall::AnyExpectations>>,
+                lenient: bool
             }
             impl GenericExpectations {
+                /// Return a default value for calls that don't match any other
+                /// expectation.  This applies to all sets of generic
+                /// parameters!
+                #v fn lenient(&mut self) {
+                    self.lenient = true;
+                }
+
                 /// Verify that all current expectations are satisfied and clear
                 /// them.  This applies to all sets of generic parameters!
                 #v fn checkpoint(&mut self) ->
@@ -2496,7 +3617,20 @@ impl<'a> ToTokens for StaticGenericExpectations<'a> {
         }
         let tbf = tg.as_turbofish();
         let output = &self.f.output;
+        let recty = &self.f.recty;
+        let calls_wc = self.f.calls_wc(wc, recty);
         let v = &self.f.privmod_vis;
+        let default = if self.f.return_ref || self.f.return_refmut {
+            // A default reference would need somewhere to live
+            quote!()
+        } else {
+            quote!(
+                None if self.lenient => ::std::result::Result::Ok(
+                    Expectations #tbf::lenient_fallback()
+                    .call(#(#argnames, )*)
+                ),
+            )
+        };
         let (call, get, self_, downcast) = if self.f.return_refmut {
             (format_ident!("call_mut"),
              format_ident!("get_mut"),
@@ -2508,29 +3642,123 @@ impl<'a> ToTokens for StaticGenericExpectations<'a> {
              quote!(&self),
              format_ident!("downcast_ref"))
         };
+        let call_default = if self.f.default_impl.is_some() {
+            let predty = &self.f.predty;
+            let call_found = format_ident!("{call}_found");
+            quote!(
+                /// Find the expectation that should handle a call with
+                /// these arguments.
+                #[allow(clippy::ptr_arg)]
+                #v fn find #ig (&self, #(#argnames: &#predty, )*) -> Found
+                    #wc
+                {
+                    self.store.get(&::mockall::Key::new::#keyid())
+                        .map(|__mockall_e| {
+                            __mockall_e.downcast_ref::<Expectations #tg>()
+                            .unwrap()
+                            .find(#(#argnames, )*)
+                        }).unwrap_or_else(|| ::std::result::Result::Err(
+                            ::std::string::String::new()))
+                }
+
+                /// Check whether the expectation that `find` chose should
+                /// call the trait's default implementation.
+                #[allow(clippy::ptr_arg)]
+                #v fn call_default #ig (&self, __mockall_found: &Found,
+                    #(#argnames: &#predty, )*) -> bool #wc
+                {
+                    self.store.get(&::mockall::Key::new::#keyid())
+                        .map(|__mockall_e| {
+                            __mockall_e.downcast_ref::<Expectations #tg>()
+                            .unwrap()
+                            .call_default(__mockall_found, #(#argnames, )*)
+                        }).unwrap_or(false)
+                }
+
+                /// Call the expectation that `find` chose.
+                #v fn #call_found #ig (#self_, __mockall_found: Found,
+                    #(#argnames: #argty, )* )
+                    -> ::std::result::Result<#output, ::std::string::String> #wc
+                {
+                    match self.store.#get(&::mockall::Key::new::#keyid()) {
+                        Some(__mockall_e) => __mockall_e
+                            .#downcast::<Expectations #tg>()
+                            .unwrap()
+                            .#call_found(__mockall_found, #(#argnames, )*),
+                        #default
+                        None => ::std::result::Result::Err(
+                            ::std::string::String::new())
+                    }
+                }
+            )
+        } else {
+            TokenStream::new()
+        };
         quote!(
             impl #ig ::mockall::AnyExpectations for Expectations #tg #any_wc {}
             impl GenericExpectations {
                 /// Simulating calling the real method.
                 #v fn #call #ig (#self_, #(#argnames: #argty, )* )
-                    -> Option<#output> #wc
+                    -> ::std::result::Result<#output, ::std::string::String> #wc
                 {
-                    self.store.#get(&::mockall::Key::new::#keyid())
+                    match self.store.#get(&::mockall::Key::new::#keyid()) {
+                        Some(__mockall_e) => __mockall_e
+                            .#downcast::<Expectations #tg>()
+                            .unwrap()
+                            .#call(#(#argnames, )*),
+                        #default
+                        None => ::std::result::Result::Err(
+                            ::std::string::String::new())
+                    }
+                }
+
+                #call_default
+
+                /// Return the arguments of every call to this method, in
+                /// order.
+                #[allow(clippy::multiple_bound_locations)]
+                #[allow(clippy::type_complexity)]
+                #v fn calls #ig (&self) -> Vec<(#(Option<#recty>,)*)>
+                    #calls_wc
+                {
+                    self.store.get(&::mockall::Key::new::#keyid())
                         .map(|__mockall_e| {
-                            __mockall_e.#downcast::<Expectations #tg>()
+                            __mockall_e.downcast_ref::<Expectations #tg>()
                             .unwrap()
-                            .#call(#(#argnames, )*)
-                        }).flatten()
+                            .calls()
+                        }).unwrap_or_default()
                 }
 
                 /// Create a new Expectation.
+                #[track_caller]
                 #v fn expect #ig (&mut self) -> &mut Expectation #tg #any_wc
                 {
+                    self.expectations #tbf().expect()
+                }
+
+                /// Return the expectation used for calls that don't match any
+                /// other, creating it if necessary.
+                #[track_caller]
+                #v fn fallback #ig (&mut self) -> &mut Expectation #tg #any_wc
+                {
+                    self.expectations #tbf().fallback()
+                }
+
+                /// Return the Expectations for this set of generic
+                /// parameters, creating them if necessary.
+                fn expectations #ig (&mut self) -> &mut Expectations #tg
+                    #any_wc
+                {
+                    let __mockall_lenient = self.lenient;
                     self.store.entry(::mockall::Key::new::#keyid())
-                        .or_insert_with(|| Box::new(Expectations #tbf::new()))
-                        .downcast_mut::<Expectations #tg>()
+                        .or_insert_with(|| {
+                            let mut __mockall_ee = Expectations #tbf::new();
+                            if __mockall_lenient {
+                                __mockall_ee.lenient();
+                            }
+                            Box::new(__mockall_ee)
+                        }).downcast_mut::<Expectations #tg>()
                         .unwrap()
-                        .expect()
                 }
             }
         ).to_tokens(tokens)
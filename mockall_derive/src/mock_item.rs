@@ -7,8 +7,8 @@ use syn::{
 };
 
 use crate::{
+    Errors,
     MockItemStruct,
-    compile_error,
     mock_function::{self, MockFunction},
     mockable_item::{MockableItem, MockableModule}
 };
@@ -19,15 +19,15 @@ pub(crate) enum MockItem {
     Struct(MockItemStruct)
 }
 
-impl From<MockableItem> for MockItem {
-    fn from(mockable: MockableItem) -> MockItem {
+impl TryFrom<MockableItem> for MockItem {
+    type Error = Error;
+
+    fn try_from(mockable: MockableItem) -> Result<Self> {
         match mockable {
-            MockableItem::Struct(s) => MockItem::Struct(
-                MockItemStruct::from(s)
-            ),
-            MockableItem::Module(mod_) => MockItem::Module(
-                MockItemModule::from(mod_)
-            )
+            MockableItem::Struct(s) => MockItemStruct::try_from(s)
+                .map(MockItem::Struct),
+            MockableItem::Module(mod_) => MockItemModule::try_from(mod_)
+                .map(MockItem::Module)
         }
     }
 }
@@ -43,6 +43,7 @@ impl ToTokens for MockItem {
 
 enum MockItemContent {
     Fn(Box<MockFunction>),
+    Mod(Box<MockItemModule>),
     Tokens(TokenStream)
 }
 
@@ -54,11 +55,14 @@ pub(crate) struct MockItemModule {
     content: Vec<MockItemContent>
 }
 
-impl From<MockableModule> for MockItemModule {
-    fn from(mod_: MockableModule) -> MockItemModule {
+impl TryFrom<MockableModule> for MockItemModule {
+    type Error = Error;
+
+    fn try_from(mod_: MockableModule) -> Result<Self> {
         let mock_ident = mod_.mock_ident.clone();
         let orig_ident = mod_.orig_ident;
         let mut content = Vec::new();
+        let mut errors = Errors::default();
         for item in mod_.content.into_iter() {
             let span = item.span();
             match item {
@@ -82,8 +86,11 @@ impl From<MockableModule> for MockItemModule {
                         .parent(&mock_ident)
                         .levels(1)
                         .call_levels(0)
+                        .thread_local(mod_.thread_local)
                         .build();
-                    content.push(MockItemContent::Fn(Box::new(mf)));
+                    if let Some(mf) = errors.ok(mf) {
+                        content.push(MockItemContent::Fn(Box::new(mf)));
+                    }
                 },
                 Item::ForeignMod(ifm) => {
                     for item in ifm.items {
@@ -102,20 +109,41 @@ impl From<MockableModule> for MockItemModule {
                                 .parent(&mock_ident)
                                 .levels(1)
                                 .call_levels(0)
+                                .thread_local(mod_.thread_local)
                                 .build();
-                            content.push(MockItemContent::Fn(Box::new(mf)));
+                            if let Some(mf) = errors.ok(mf) {
+                                content.push(
+                                    MockItemContent::Fn(Box::new(mf))
+                                );
+                            }
                         } else {
-                            compile_error(item.span(),
-                                "Mockall does not yet support  this type in this position.  Please open an issue with your use case at https://github.com/asomers/mockall");
+                            errors.push(Error::new(item.span(),
+                                "Mockall does not yet support  this type in this position.  Please open an issue with your use case at https://github.com/asomers/mockall"));
                         }
                     }
                 },
-                Item::Mod(_)
-                    | Item::Struct(_) | Item::Enum(_)
+                Item::Mod(item_mod) => {
+                    // A nested mock module keeps its original name, so that
+                    // swapping the outermost module swaps the whole tree.
+                    let cfgs = item_mod.attrs.iter()
+                        .filter(|attr| attr.path().is_ident("cfg"))
+                        .collect::<Vec<_>>();
+                    let submod = MockableModule::try_from(item_mod.clone())
+                        .and_then(|mut submod| {
+                            submod.attrs = quote!(#(#cfgs)*);
+                            submod.mock_ident = item_mod.ident;
+                            submod.thread_local = mod_.thread_local;
+                            MockItemModule::try_from(submod)
+                        });
+                    if let Some(submod) = errors.ok(submod) {
+                        content.push(MockItemContent::Mod(Box::new(submod)));
+                    }
+                },
+                Item::Struct(_) | Item::Enum(_)
                     | Item::Union(_) | Item::Trait(_) =>
                 {
-                    compile_error(span,
-                        "Mockall does not yet support deriving nested mocks");
+                    errors.push(Error::new(span,
+                        "Mockall does not yet support deriving nested mocks"));
                 },
                 Item::Type(ty) => {
                     content.push(
@@ -132,16 +160,16 @@ impl From<MockableModule> for MockItemModule {
                         MockItemContent::Tokens(u.into_token_stream())
                     );
                 },
-                _ => compile_error(span, "Unsupported item")
+                _ => errors.push(Error::new(span, "Unsupported item"))
             }
         }
-        MockItemModule {
+        errors.finish(MockItemModule {
             attrs: mod_.attrs,
             vis: mod_.vis,
             mock_ident: mod_.mock_ident,
             orig_ident,
             content
-        }
+        })
     }
 }
 
@@ -167,6 +195,13 @@ impl ToTokens for MockItemModule {
                     ).to_tokens(&mut body);
                     f.checkpoint().to_tokens(&mut cp_body);
                 },
+                MockItemContent::Mod(m) => {
+                    m.to_tokens(&mut body);
+                    let attrs = &m.attrs;
+                    let ident = &m.mock_ident;
+                    quote!(#attrs { #ident::checkpoint(); })
+                        .to_tokens(&mut cp_body);
+                }
             }
         }
 
@@ -2,16 +2,13 @@
 use std::collections::HashSet;
 
 use proc_macro2::TokenStream;
-use quote::{ToTokens, format_ident, quote};
-use syn::{
-    *,
-    spanned::Spanned
-};
+use quote::{ToTokens, format_ident, quote, quote_spanned};
+use syn::*;
 
 use crate::{
     AttrFormatter,
+    Errors,
     MockableStruct,
-    compile_error,
     gen_mod_ident,
     mock_function::{self, MockFunction},
     mock_trait::MockTrait
@@ -21,6 +18,7 @@ fn phantom_default_inits(generics: &Generics) -> Vec<TokenStream> {
     generics.params
     .iter()
     .enumerate()
+    .filter(|(_count, param)| !matches!(param, GenericParam::Const(_)))
     .map(|(count, _param)| {
         let phident = format_ident!("_t{count}");
         quote!(#phident: ::std::marker::PhantomData)
@@ -36,10 +34,6 @@ fn phantom_fields(generics: &Generics) -> Vec<TokenStream> {
         let phident = format_ident!("_t{count}");
         match param {
             syn::GenericParam::Lifetime(l) => {
-                if !l.bounds.is_empty() {
-                    compile_error(l.bounds.span(),
-                        "#automock does not yet support lifetime bounds on structs");
-                }
                 let lifetime = &l.lifetime;
                 Some(
                 quote!(#phident: ::std::marker::PhantomData<&#lifetime ()>)
@@ -51,11 +45,8 @@ fn phantom_fields(generics: &Generics) -> Vec<TokenStream> {
                 quote!(#phident: ::std::marker::PhantomData<#ty>)
                 )
             },
-            syn::GenericParam::Const(_) => {
-                compile_error(param.span(),
-                    "#automock does not yet support generic constants");
-                None
-            }
+            // Unused const parameters are allowed, so they need no field
+            syn::GenericParam::Const(_) => None
         }
     }).collect()
 }
@@ -99,6 +90,13 @@ impl Methods {
             .collect::<Vec<_>>()
     }
 
+    fn lenients(&self) -> Vec<impl ToTokens> {
+        self.0.iter()
+            .filter(|meth| !meth.is_static())
+            .map(|meth| meth.lenient())
+            .collect::<Vec<_>>()
+    }
+
     /// Return a fragment of code to initialize struct fields during default()
     fn default_inits(&self) -> Vec<TokenStream> {
         self.0.iter()
@@ -129,11 +127,16 @@ impl Methods {
 pub(crate) struct MockItemStruct {
     attrs: Vec<Attribute>,
     consts: Vec<ImplItemConst>,
+    /// Helper trait holding the default method implementations of the
+    /// mocked trait, if any
+    defaults: Option<Box<ItemTrait>>,
     generics: Generics,
     /// Should Mockall generate a Debug implementation?
     auto_debug: bool,
     /// Does the original struct have a `new` method?
     has_new: bool,
+    /// Does the original struct have a `shared` method?
+    has_shared: bool,
     /// Inherent methods of the mock struct
     methods: Methods,
     /// Name of the overall module that holds all of the mock stuff
@@ -164,6 +167,106 @@ impl MockItemStruct {
         }
     }
 
+    /// Generate the helper trait that holds the mocked trait's default method
+    /// implementations, and implement it for the mock struct.
+    fn defaults_impl(&self) -> impl ToTokens {
+        if let Some(defaults) = &self.defaults {
+            let ident = &defaults.ident;
+            let (ig, tg, wc) = self.generics.split_for_impl();
+            let struct_name = &self.name;
+            quote!(
+                #defaults
+                impl #ig #ident #tg for #struct_name #tg #wc {}
+            )
+        } else {
+            quote!()
+        }
+    }
+
+    /// Generate the shared handle type and its trait implementations, plus the
+    /// mock struct's `shared` method.
+    ///
+    /// The handle only implements the traits whose every method it can
+    /// forward.  The others are listed in its documentation.
+    fn shared(&self) -> (TokenStream, TokenStream) {
+        let mut traits = Vec::new();
+        let mut unshared_doc = String::new();
+        for trait_ in self.traits.iter().filter(|trait_| !trait_.is_clone()) {
+            if let Some(meth) = trait_.unshareable_method() {
+                unshared_doc.push_str(&format!("\n\nIt doesn't implement \
+                    `{}`, because it can't forward the `{}` method.",
+                    trait_.trait_path.segments.last().unwrap().ident, meth));
+            } else {
+                traits.push(trait_);
+            }
+        }
+        let cfgs = self.attrs.iter()
+            .filter(|attr| attr.path().is_ident("cfg"))
+            .collect::<Vec<_>>();
+        let (ig, tg, wc) = self.generics.split_for_impl();
+        let struct_name = &self.name;
+        let shared_name = format_ident!("Shared{}", self.name);
+        let vis = &self.vis;
+        let shared_impls = traits.iter()
+            .map(|trait_| trait_.shared_impl(&shared_name))
+            .collect::<Vec<_>>();
+        let struct_docstr = format!("A cheaply cloneable handle to a shared [`{struct_name}`].
+
+Every clone forwards calls to the same mock object.  So the test can keep one clone to set expectations on and checkpoint, while the code under test owns another.{unshared_doc}");
+        let shared_method = if self.has_shared {
+            TokenStream::new()
+        } else {
+            let docstr = format!("Create a new mock object with no expectations, \
+                wrapped in a [`{shared_name}`] handle.
+
+This method will not be generated if the real struct already has a `shared` \
+method.");
+            quote!(
+                #[doc = #docstr]
+                pub fn shared() -> #shared_name #tg {
+                    #shared_name::from(Self::default())
+                }
+            )
+        };
+        let shared_struct = quote!(
+            #(#cfgs)*
+            #[doc = #struct_docstr]
+            #[allow(dead_code)]
+            #vis struct #shared_name #ig (
+                ::std::sync::Arc<::std::sync::Mutex<#struct_name #tg>>
+            ) #wc;
+            #(#cfgs)*
+            #[allow(dead_code)]
+            impl #ig #shared_name #tg #wc {
+                /// Lock the mock object, in order to set expectations on it
+                /// or checkpoint it.
+                ///
+                /// Calling the mock's methods through any handle will block
+                /// until the returned guard is dropped.
+                pub fn lock(&self) -> ::std::sync::MutexGuard<'_, #struct_name #tg> {
+                    self.0.lock()
+                        .unwrap_or_else(::std::sync::PoisonError::into_inner)
+                }
+            }
+            #(#cfgs)*
+            impl #ig ::std::clone::Clone for #shared_name #tg #wc {
+                fn clone(&self) -> Self {
+                    Self(self.0.clone())
+                }
+            }
+            #(#cfgs)*
+            impl #ig ::std::convert::From<#struct_name #tg> for #shared_name #tg
+                #wc
+            {
+                fn from(mock: #struct_name #tg) -> Self {
+                    Self(::std::sync::Arc::new(::std::sync::Mutex::new(mock)))
+                }
+            }
+            #(#shared_impls)*
+        );
+        (shared_struct, shared_method)
+    }
+
     fn new_method(&self) -> impl ToTokens {
         if self.has_new {
             TokenStream::new()
@@ -192,52 +295,64 @@ impl MockItemStruct {
     }
 }
 
-impl From<MockableStruct> for MockItemStruct {
-    fn from(mockable: MockableStruct) -> MockItemStruct {
+impl TryFrom<MockableStruct> for MockItemStruct {
+    type Error = Error;
+
+    fn try_from(mockable: MockableStruct) -> Result<Self> {
+        let mut errors = Errors::default();
         let auto_debug = mockable.derives_debug();
         let modname = gen_mod_ident(&mockable.name, None);
         let generics = mockable.generics.clone();
         let struct_name = &mockable.name;
         let vis = mockable.vis;
-        let has_new = mockable.methods.iter()
-            .any(|meth| meth.sig.ident == "new") ||
+        let has_method = |name: &str| mockable.methods.iter()
+            .any(|meth| meth.sig.ident == name) ||
             mockable.impls.iter()
             .any(|impl_|
                 impl_.items.iter()
                     .any(|ii| if let ImplItem::Fn(iif) = ii {
-                            iif.sig.ident == "new"
+                            iif.sig.ident == name
                         } else {
                             false
                         }
                     )
             );
+        let has_new = has_method("new");
+        let has_shared = has_method("shared");
         let methods = Methods(mockable.methods.into_iter()
-            .map(|meth|
+            .filter_map(|meth|
                 mock_function::Builder::new(&meth.sig, &meth.vis)
                     .attrs(&meth.attrs)
                     .struct_(struct_name)
                     .struct_generics(&generics)
                     .levels(2)
                     .call_levels(0)
+                    .thread_local(mockable.thread_local)
                     .build()
+                    .map_err(|e| errors.push(e))
+                    .ok()
             ).collect::<Vec<_>>());
         let structname = &mockable.name;
+        let defaults = mockable.defaults;
         let traits = mockable.impls.into_iter()
-            .map(|i| MockTrait::new(structname, &generics, i, &vis))
+            .filter_map(|i| errors.ok(MockTrait::new(structname, &generics, i,
+                &vis, defaults.as_deref(), mockable.thread_local)))
             .collect();
 
-        MockItemStruct {
+        errors.finish(MockItemStruct {
             attrs: mockable.attrs,
             auto_debug,
             consts: mockable.consts,
+            defaults,
             generics,
             has_new,
+            has_shared,
             methods,
             modname,
             name: mockable.name,
             traits,
             vis
-        }
+        })
     }
 }
 
@@ -249,6 +364,7 @@ impl ToTokens for MockItemStruct {
             .format();
         let consts = &self.consts;
         let debug_impl = self.debug_impl();
+        let defaults_impl = self.defaults_impl();
         let struct_name = &self.name;
         let (ig, tg, wc) = self.generics.split_for_impl();
         let modname = &self.modname;
@@ -263,8 +379,18 @@ impl ToTokens for MockItemStruct {
             .filter(|meth| !meth.is_static())
             .map(|meth| meth.expect(modname, None))
             .collect::<Vec<_>>();
+        let fallbacks = self.methods.0.iter()
+            .filter(|meth| !meth.is_static())
+            .map(|meth| meth.fallback(modname, None))
+            .collect::<Vec<_>>();
+        let calls_fns = self.methods.0.iter()
+            .filter(|meth| !meth.is_static())
+            .map(|meth| meth.calls_fn())
+            .collect::<Vec<_>>();
         let method_checkpoints = self.methods.checkpoints();
+        let method_lenients = self.methods.lenients();
         let new_method = self.new_method();
+        let (shared_struct, shared_method) = self.shared();
         let priv_mods = self.methods.priv_mods();
         let substructs = unique_trait_iter(self.traits.iter())
             .map(|trait_| {
@@ -289,6 +415,16 @@ impl ToTokens for MockItemStruct {
                 let fieldname = &ss.fieldname;
                 quote!(#(#attrs)* self.#fieldname.checkpoint();)
             }).collect::<Vec<_>>();
+        let substruct_lenients = substructs.iter()
+            .filter(|ss| !ss.all_static())
+            .map(|ss| {
+                let attrs = AttrFormatter::new(&ss.attrs)
+                    .async_trait(false)
+                    .doc(false)
+                    .format();
+                let fieldname = &ss.fieldname;
+                quote!(#(#attrs)* __mockall_mock.#fieldname.lenient();)
+            }).collect::<Vec<_>>();
         let mut field_definitions = substructs.iter()
             .filter(|ss| !ss.all_static())
             .map(|ss| {
@@ -321,7 +457,18 @@ impl ToTokens for MockItemStruct {
                 trait_.trait_impl(&modname)
             }).collect::<Vec<_>>();
         let vis = &self.vis;
+        // The mock always lives in the invoking module, whatever module the
+        // original type is in.  If two mocks there would share a name, make
+        // rustc say so at the original type's name.
+        let marker = format_ident!("__mockall_{}_is_already_defined_here",
+                                   struct_name);
+        let marker = quote_spanned!(struct_name.span()=>
+            #[allow(dead_code)]
+            #[allow(non_upper_case_globals)]
+            const #marker: () = ();
+        );
         quote!(
+            #marker
             #[allow(non_snake_case)]
             #[allow(missing_docs)]
             pub mod #modname {
@@ -351,6 +498,8 @@ impl ToTokens for MockItemStruct {
                 #(#calls)*
                 #(#contexts)*
                 #(#expects)*
+                #(#fallbacks)*
+                #(#calls_fns)*
                 /// Validate that all current expectations for all methods have
                 /// been satisfied, and discard them.
                 pub fn checkpoint(&mut self) {
@@ -358,8 +507,23 @@ impl ToTokens for MockItemStruct {
                     #(#method_checkpoints)*
                 }
                 #new_method
+                #shared_method
+                /// Create a new lenient mock object with no expectations.
+                ///
+                /// Calls to a lenient mock's methods that don't match any
+                /// expectation will return a default value instead of
+                /// panicking.  Static methods are not affected.
+                pub fn new_lenient() -> Self {
+                    #[allow(unused_mut)]
+                    let mut __mockall_mock = Self::default();
+                    #(#substruct_lenients)*
+                    #(#method_lenients)*
+                    __mockall_mock
+                }
             }
             #(#trait_impls)*
+            #defaults_impl
+            #shared_struct
         ).to_tokens(tokens);
     }
 }
@@ -410,6 +574,7 @@ impl ToTokens for MockItemTraitImpl {
         let (ig, tg, wc) = self.generics.split_for_impl();
         let modname = &self.modname;
         let method_checkpoints = self.methods.checkpoints();
+        let method_lenients = self.methods.lenients();
         let mut default_inits = self.methods.default_inits();
         default_inits.extend(self.phantom_default_inits());
         let mut field_definitions = self.methods.field_definitions(modname);
@@ -446,6 +611,11 @@ impl ToTokens for MockItemTraitImpl {
                 pub fn checkpoint(&mut self) {
                     #(#method_checkpoints)*
                 }
+                /// Make every method of this substructure lenient.
+                pub fn lenient(&mut self) {
+                    let __mockall_mock = self;
+                    #(#method_lenients)*
+                }
             }
         ).to_tokens(tokens);
     }
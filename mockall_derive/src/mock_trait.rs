@@ -1,5 +1,4 @@
 // vim: tw=80
-use proc_macro2::Span;
 use quote::{ToTokens, format_ident, quote};
 use std::{
    collections::hash_map::DefaultHasher,
@@ -12,8 +11,9 @@ use syn::{
 
 use crate::{
     AttrFormatter,
+    Errors,
     mock_function::{self, MockFunction},
-    compile_error
+    gen_default_fn_ident
 };
 
 pub(crate) struct MockTrait {
@@ -48,6 +48,51 @@ impl MockTrait {
         }
     }
 
+    /// Return the first of this trait's methods that a shared handle can't
+    /// forward, if any.
+    pub fn unshareable_method(&self) -> Option<&Ident> {
+        self.methods.iter()
+            .find(|meth| !meth.is_shareable())
+            .map(MockFunction::name)
+    }
+
+    /// Is this the `Clone` trait?  The shared handle has its own `Clone`
+    /// implementation instead.
+    pub fn is_clone(&self) -> bool {
+        self.trait_path.segments.last().unwrap().ident == "Clone"
+    }
+
+    /// Generate code for the trait implementation on the shared handle, which
+    /// forwards every method to the mock struct.
+    ///
+    /// # Arguments
+    ///
+    /// * `shared_name`:    Name of the shared handle type
+    pub fn shared_impl(&self, shared_name: &Ident) -> impl ToTokens {
+        let trait_impl_attrs = AttrFormatter::new(&self.attrs)
+            .must_use(false)
+            .format();
+        let (ig, _tg, wc) = self.generics.split_for_impl();
+        let consts = &self.consts;
+        let trait_path = &self.trait_path;
+        let self_path = &self.self_path;
+        let mut shared_path = self.self_path.clone();
+        shared_path.ident = shared_name.clone();
+        let calls = self.methods.iter()
+            .map(|meth| meth.shared_call(self_path, trait_path))
+            .collect::<Vec<_>>();
+        let types = &self.types;
+        let unsafety = &self.unsafety;
+        quote!(
+            #(#trait_impl_attrs)*
+            #unsafety impl #ig #trait_path for #shared_path #wc {
+                #(#consts)*
+                #(#types)*
+                #(#calls)*
+            }
+        )
+    }
+
     pub fn ss_name(&self) -> &Ident {
         &self.ss_name
     }
@@ -59,28 +104,37 @@ impl MockTrait {
     /// * `struct_generics` - Generics of the parent structure
     /// * `impl_`  -    Mockable ItemImpl for a trait
     /// * `vis`     -   Visibility of the struct
+    /// * `defaults` -  Helper trait holding the trait's default methods, if any
+    /// * `thread_local` - Store static methods' expectations per thread
     pub fn new(structname: &Ident,
                struct_generics: &Generics,
                impl_: ItemImpl,
-               vis: &Visibility) -> Self
+               vis: &Visibility,
+               defaults: Option<&ItemTrait>,
+               thread_local: bool) -> Result<Self>
     {
         let mut consts = Vec::new();
         let mut methods = Vec::new();
         let mut types = Vec::new();
+        let mut errors = Errors::default();
         let trait_path = if let Some((_, path, _)) = impl_.trait_ {
             path
         } else {
-            compile_error(impl_.span(), "impl block must implement a trait");
-            Path::from(format_ident!("__mockall_invalid"))
+            return Err(Error::new(impl_.span(),
+                "impl block must implement a trait"));
         };
         let ss_name = MockTrait::ss_name_priv(&trait_path);
+        let defaults_path = defaults.map(|defaults| {
+            let mut path = trait_path.clone();
+            path.segments.last_mut().unwrap().ident = defaults.ident.clone();
+            path
+        });
         let self_path = match *impl_.self_ty {
             Type::Path(mut type_path) =>
                 type_path.path.segments.pop().unwrap().into_value(),
             x => {
-                compile_error(x.span(),
-                    "mockall_derive only supports mocking traits and structs");
-                PathSegment::from(Ident::new("", Span::call_site()))
+                return Err(Error::new(x.span(),
+                    "mockall_derive only supports mocking traits and structs"));
             }
         };
 
@@ -90,26 +144,34 @@ impl MockTrait {
                     consts.push(iic);
                 },
                 ImplItem::Fn(iif) => {
-                    let mf = mock_function::Builder::new(&iif.sig, vis)
-                        .attrs(&iif.attrs)
+                    let mut builder = mock_function::Builder::new(&iif.sig, vis);
+                    builder.attrs(&iif.attrs)
                         .levels(2)
                         .call_levels(0)
                         .struct_(structname)
                         .struct_generics(struct_generics)
-                        .trait_(&ss_name)
-                        .build();
-                    methods.push(mf);
+                        .thread_local(thread_local)
+                        .trait_(&ss_name);
+                    let default_fn = gen_default_fn_ident(&iif.sig.ident);
+                    let has_default = defaults.is_some_and(|d|
+                        d.items.iter().any(|ti| matches!(ti,
+                            TraitItem::Fn(tif) if tif.sig.ident == default_fn))
+                    );
+                    if has_default {
+                        builder.default_impl(defaults_path.as_ref().unwrap());
+                    }
+                    methods.extend(errors.ok(builder.build()));
                 },
                 ImplItem::Type(iit) => {
                     types.push(iit);
                 },
                 _ => {
-                    compile_error(ii.span(),
-                    "This impl item is not yet supported by MockAll");
+                    errors.push(Error::new(ii.span(),
+                    "This impl item is not yet supported by MockAll"));
                 }
             }
         }
-        MockTrait {
+        errors.finish(MockTrait {
             attrs: impl_.attrs,
             consts,
             generics: impl_.generics,
@@ -119,7 +181,7 @@ impl MockTrait {
             self_path,
             types,
             unsafety: impl_.unsafety
-        }
+        })
     }
 
     /// Generate code for the trait implementation on the mock struct
@@ -157,6 +219,19 @@ impl MockTrait {
                     meth.expect(modname, Some(path_args))
                 }
             }).collect::<Vec<_>>();
+        let fallbacks = self.methods.iter()
+            .filter(|meth| !meth.is_static())
+            .map(|meth| {
+                if meth.is_method_generic() {
+                    meth.fallback(modname, None)
+                } else {
+                    meth.fallback(modname, Some(path_args))
+                }
+            }).collect::<Vec<_>>();
+        let calls_fns = self.methods.iter()
+            .filter(|meth| !meth.is_static())
+            .map(|meth| meth.calls_fn())
+            .collect::<Vec<_>>();
         let trait_path = &self.trait_path;
         let self_path = &self.self_path;
         let types = &self.types;
@@ -171,6 +246,8 @@ impl MockTrait {
             #(#impl_attrs)*
             impl #ig #self_path #wc {
                 #(#expects)*
+                #(#fallbacks)*
+                #(#calls_fns)*
                 #(#contexts)*
             }
         )
@@ -1,21 +1,149 @@
 // vim: tw=80
+use std::{
+    fs,
+    path::{Path, PathBuf}
+};
+
+use syn::ext::IdentExt;
+
 use super::*;
 
 /// Performs transformations on a function to make it mockable
-fn mockable_fn(mut item_fn: ItemFn) -> ItemFn {
-    demutify(&mut item_fn.sig.inputs);
+fn mockable_fn(mut item_fn: ItemFn) -> Result<ItemFn> {
+    demutify(&mut item_fn.sig.inputs)?;
     deimplify(&mut item_fn.sig.output);
-    item_fn
+    Ok(item_fn)
 }
 
 /// Performs transformations on an Item to make it mockable
-fn mockable_item(item: Item) -> Item {
+fn mockable_item(item: Item) -> Result<Item> {
     match item {
-        Item::Fn(item_fn) => Item::Fn(mockable_fn(item_fn)),
-        x => x
+        Item::Fn(item_fn) => mockable_fn(item_fn).map(Item::Fn),
+        x => Ok(x)
     }
 }
 
+/// Return the value of a module's `#[path]` attribute, if any.
+fn path_attr(mod_: &ItemMod) -> Option<String> {
+    mod_.attrs.iter()
+        .filter(|attr| attr.path().is_ident("path"))
+        .find_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue{
+                value: Expr::Lit(ExprLit{lit: Lit::Str(s), ..}), ..
+            }) => Some(s.value()),
+            _ => None
+        })
+}
+
+/// Find the directories that may contain the source of out-of-line modules
+/// declared by the macro's caller.
+///
+/// Like rustc, look relative to the file that declares the module, and obey
+/// any `#[path]` attribute.  Returns `None` if the compiler can't tell a proc
+/// macro which file it was called from, as is the case before Rust 1.88.
+fn caller_dirs(mod_: &ItemMod) -> Option<Vec<PathBuf>> {
+    let file = Span::call_site().local_file()?;
+    let parent = file.parent()?;
+    let stem = file.file_stem()?;
+    let mod_rs = ["lib", "main", "mod"].iter().any(|s| stem == *s);
+    if path_attr(mod_).is_some() || mod_rs {
+        Some(vec![parent.to_owned()])
+    } else {
+        // The declaring file is either a crate root, like tests/foo.rs, or
+        // a non-mod.rs module file, like src/foo.rs
+        Some(vec![parent.join(stem), parent.to_owned()])
+    }
+}
+
+/// Find the file that contains an out-of-line module's source, searching each
+/// of `dirs`.
+fn module_file(mod_: &ItemMod, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let candidates = if let Some(p) = path_attr(mod_) {
+        vec![PathBuf::from(p)]
+    } else {
+        let name = mod_.ident.unraw().to_string();
+        vec![
+            PathBuf::from(format!("{name}.rs")),
+            [&name, "mod.rs"].iter().collect()
+        ]
+    };
+    dirs.iter()
+        .flat_map(|d| candidates.iter().map(move |c| d.join(c)))
+        .find(|p| p.is_file())
+}
+
+/// Load the items of an out-of-line module from its source file.
+///
+/// `dirs` are the directories that may contain the module's source file, or
+/// `None` if they're unknown.  In that case, the module must have a `#[path]`
+/// attribute, relative to the package's root directory.
+///
+/// Returns the items, and the directories that may contain the sources of
+/// the module's own out-of-line submodules.
+fn module_items(mod_: &ItemMod, dirs: Option<&[PathBuf]>)
+    -> Result<(Vec<Item>, Vec<PathBuf>)>
+{
+    let span = mod_.span();
+    let path = if let Some(dirs) = dirs {
+        module_file(mod_, dirs)
+    } else {
+        let Some(p) = path_attr(mod_) else {
+            return Err(Error::new(span, format!(
+                "mockall can't tell which file declares module `{}`, because this compiler is older than 1.88.  Add a #[path] attribute, relative to the package's root directory",
+                mod_.ident)));
+        };
+        env::var_os("CARGO_MANIFEST_DIR")
+            .map(|root| PathBuf::from(root).join(p))
+            .filter(|p| p.is_file())
+    }.ok_or_else(|| Error::new(span,
+        format!("automock could not find the source file for module `{}`",
+            mod_.ident)))?;
+    let items = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|src| parse_file(&src).map_err(|e| e.to_string()))
+        .map_err(|e| Error::new(span,
+            format!("automock could not load {}: {e}", path.display())))?
+        .items;
+    let parent = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let child_dirs = match path.file_stem() {
+        Some(stem) if stem != "mod" => vec![parent.join(stem)],
+        _ => vec![parent]
+    };
+    Ok((items, child_dirs))
+}
+
+/// Replace a module's content with its items, loading them from the module's
+/// source file if necessary.  Do the same for all of its submodules.
+///
+/// `dirs` are the directories that may contain the module's source file, if
+/// known.  Like rustc, treat an inline module's `#[path]` attribute as the
+/// name of the directory that contains its own submodules.
+fn inline_module(mod_: &mut ItemMod, dirs: Option<&[PathBuf]>) -> Result<()>
+{
+    let (items, child_dirs) = if let Some((_, content)) = mod_.content.take() {
+        let name = path_attr(mod_)
+            .unwrap_or_else(|| mod_.ident.unraw().to_string());
+        let child_dirs = dirs.map(|dirs| {
+            dirs.iter().map(|d| d.join(&name)).collect::<Vec<_>>()
+        });
+        (content, child_dirs)
+    } else {
+        let (items, child_dirs) = module_items(mod_, dirs)?;
+        (items, Some(child_dirs))
+    };
+    let mut errors = Errors::default();
+    let items = items.into_iter()
+        .map(|item| match item {
+            Item::Mod(mut submod) => {
+                errors.ok(inline_module(&mut submod, child_dirs.as_deref()));
+                Item::Mod(submod)
+            },
+            item => item
+        }).collect();
+    mod_.content = Some((token::Brace::default(), items));
+    errors.finish(())
+}
+
 /// An item that's ready to be mocked.
 ///
 /// It should be functionally identical or near-identical to the original item,
@@ -26,16 +154,25 @@ pub(crate) enum MockableItem {
     Struct(MockableStruct)
 }
 
-impl From<(Attrs, Item)> for MockableItem {
-    fn from((attrs, item): (Attrs, Item)) -> MockableItem {
+impl TryFrom<(Attrs, Item)> for MockableItem {
+    type Error = Error;
+
+    fn try_from((attrs, item): (Attrs, Item)) -> Result<Self> {
         match item {
-            Item::Impl(item_impl) =>
-                MockableItem::Struct(MockableStruct::from(item_impl)),
-            Item::Mod(item_mod) =>
-                MockableItem::Module(MockableModule::from(item_mod)),
-            Item::Trait(trait_) =>
-                MockableItem::Struct(MockableStruct::from((attrs, trait_))),
-            _ => panic!("automock does not support this item type")
+            Item::Impl(item_impl) => {
+                let mut mockable = MockableStruct::try_from(item_impl)?;
+                mockable.thread_local = attrs.thread_local;
+                Ok(MockableItem::Struct(mockable))
+            },
+            Item::Mod(item_mod) => {
+                let mut mockable = MockableModule::try_from(item_mod)?;
+                mockable.thread_local = attrs.thread_local;
+                Ok(MockableItem::Module(mockable))
+            },
+            Item::Trait(trait_) => MockableStruct::try_from((attrs, trait_))
+                .map(MockableItem::Struct),
+            _ => Err(Error::new(item.span(),
+                "automock does not support this item type"))
         }
     }
 }
@@ -52,30 +189,34 @@ pub(crate) struct MockableModule {
     pub mock_ident: Ident,
     /// Ident of the original module, if any
     pub orig_ident: Option<Ident>,
-    pub content: Vec<Item>
+    pub content: Vec<Item>,
+    /// Are the functions' expectations stored per thread?
+    pub thread_local: bool
 }
 
-impl From<ItemMod> for MockableModule {
-    fn from(mod_: ItemMod) -> MockableModule {
-        let span = mod_.span();
+impl TryFrom<ItemMod> for MockableModule {
+    type Error = Error;
+
+    fn try_from(mut mod_: ItemMod) -> Result<Self> {
+        let dirs = caller_dirs(&mod_);
+        inline_module(&mut mod_, dirs.as_deref())?;
+        let mut errors = Errors::default();
+        let content = mod_.content.take()
+            .map(|(_, content)| content)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|item| errors.ok(mockable_item(item)))
+            .collect();
         let vis = mod_.vis;
         let mock_ident = format_ident!("mock_{}", mod_.ident);
         let orig_ident = Some(mod_.ident);
-        let content = if let Some((_, content)) = mod_.content {
-            content.into_iter()
-            .map(mockable_item)
-            .collect()
-        } else {
-            compile_error(span,
-            "automock can only mock inline modules, not modules from another file");
-            Vec::new()
-        };
-        MockableModule {
+        errors.finish(MockableModule {
             attrs: TokenStream::new(),
             vis,
             mock_ident,
             orig_ident,
-            content
-        }
+            content,
+            thread_local: false
+        })
     }
 }
@@ -1,6 +1,6 @@
 // vim: tw=80
-use proc_macro2::{Span, TokenStream};
-use quote::{format_ident, quote};
+use proc_macro2::Span;
+use quote::quote;
 use syn::{
     *,
     parse::{Parse, ParseStream},
@@ -9,7 +9,7 @@ use syn::{
 
 use crate::{
     Attrs,
-    compile_error,
+    Errors,
     deanonymize,
     deimplify,
     demutify,
@@ -17,13 +17,19 @@ use crate::{
     deselfify_args,
     dewhereselfify,
     find_ident_from_path,
+    gen_default_fn_ident,
+    gen_default_trait_ident,
     gen_mock_ident,
     AttrFormatter,
 };
 
 /// Make any implicit lifetime parameters explicit
-fn add_lifetime_parameters(sig: &mut Signature) {
-    fn add_to_trait_object(generics: &mut Generics, var: &Pat, to: &mut TypeTraitObject) {
+fn add_lifetime_parameters(sig: &mut Signature) -> Result<()> {
+    fn add_to_trait_object(
+        generics: &mut Generics,
+        var: &Pat,
+        to: &mut TypeTraitObject) -> Result<()>
+    {
         let mut has_lifetime = false;
         for bound in to.bounds.iter() {
             if let TypeParamBound::Lifetime(_) = bound {
@@ -33,25 +39,23 @@ fn add_lifetime_parameters(sig: &mut Signature) {
         if ! has_lifetime {
             let arg_ident = match *var {
                 Pat::Wild(_) => {
-                    compile_error(var.span(),
-                        "Mocked methods must have named arguments");
-                    format_ident!("dont_care")
+                    return Err(Error::new(var.span(),
+                        "Mocked methods must have named arguments"));
                 },
                 Pat::Ident(ref pat_ident) => {
                     if let Some(r) = &pat_ident.by_ref {
-                        compile_error(r.span(),
-                            "Mockall does not support by-reference argument bindings");
+                        return Err(Error::new(r.span(),
+                            "Mockall does not support by-reference argument bindings"));
                     }
                     if let Some((_at, subpat)) = &pat_ident.subpat {
-                        compile_error(subpat.span(),
-                            "Mockall does not support subpattern bindings");
+                        return Err(Error::new(subpat.span(),
+                            "Mockall does not support subpattern bindings"));
                     }
-                    pat_ident.ident.clone()
+                    &pat_ident.ident
                 },
                 _ => {
-                    compile_error(var.span(),
-                        "Unsupported argument type");
-                    format_ident!("dont_care")
+                    return Err(Error::new(var.span(),
+                        "Unsupported argument type"));
                 }
             };
             let s = format!("'__mockall_{arg_ident}");
@@ -63,26 +67,29 @@ fn add_lifetime_parameters(sig: &mut Signature) {
             let gpl = GenericParam::Lifetime(LifetimeParam::new(lt));
             generics.params.push(gpl);
         }
+        Ok(())
     }
 
-    fn add_to_type(generics: &mut Generics, var: &Pat, ty: &mut Type) {
+    fn add_to_type(generics: &mut Generics, var: &Pat, ty: &mut Type)
+        -> Result<()>
+    {
         match ty {
             Type::Array(ta) => add_to_type(generics, var, ta.elem.as_mut()),
-            Type::BareFn(_) => (),
-            Type::ImplTrait(_) => (),
-            Type::Path(_) => (),
-            Type::Ptr(_) => (),
+            Type::BareFn(_) => Ok(()),
+            Type::ImplTrait(_) => Ok(()),
+            Type::Path(_) => Ok(()),
+            Type::Ptr(_) => Ok(()),
             Type::Reference(tr) => {
                 match tr.elem.as_mut() {
                     Type::Paren(tp) => {
                         if let Type::TraitObject(to) = tp.elem.as_mut() {
-                            add_to_trait_object(generics, var, to);
+                            add_to_trait_object(generics, var, to)
                         } else {
-                            add_to_type(generics, var, tr.elem.as_mut());
+                            add_to_type(generics, var, tr.elem.as_mut())
                         }
                     },
                     Type::TraitObject(to) => {
-                        add_to_trait_object(generics, var, to);
+                        add_to_trait_object(generics, var, to)?;
                         // We need to wrap it in a Paren.  Otherwise it won't be
                         // syntactically valid after we add a lifetime bound,
                         // due to a "ambiguous `+` in a type" error
@@ -90,6 +97,7 @@ fn add_lifetime_parameters(sig: &mut Signature) {
                             paren_token: token::Paren::default(),
                             elem: Box::new(Type::TraitObject(to.clone()))
                         });
+                        Ok(())
                     },
                     _ => add_to_type(generics, var, tr.elem.as_mut()),
                 }
@@ -97,18 +105,21 @@ fn add_lifetime_parameters(sig: &mut Signature) {
             Type::Slice(ts) => add_to_type(generics, var, ts.elem.as_mut()),
             Type::Tuple(tt) => {
                 for ty in tt.elems.iter_mut() {
-                    add_to_type(generics, var, ty)
+                    add_to_type(generics, var, ty)?
                 }
+                Ok(())
             },
-            _ => compile_error(ty.span(), "unsupported type in this position")
+            _ => Err(Error::new(ty.span(), "unsupported type in this position"))
         }
     }
 
+    let mut errors = Errors::default();
     for arg in sig.inputs.iter_mut() {
         if let FnArg::Typed(pt) = arg {
-            add_to_type(&mut sig.generics, &pt.pat, &mut pt.ty)
+            errors.ok(add_to_type(&mut sig.generics, &pt.pat, &mut pt.ty));
         }
     }
+    errors.finish(())
 }
 
 /// Generate a #[derive(Debug)] Attribute
@@ -122,93 +133,116 @@ fn derive_debug() -> Attribute {
     }
 }
 
-/// Add "Mock" to the front of the named type
-fn mock_ident_in_type(ty: &mut Type) {
+/// Replace the named type with its mock, which is always defined in the
+/// current module.  For example, `crate::a::Foo<T>` becomes `MockFoo<T>`.
+fn mock_ident_in_type(ty: &mut Type) -> Result<()> {
     match ty {
         Type::Path(type_path) => {
-            if type_path.path.segments.len() != 1 {
-                compile_error(type_path.path.span(),
-                    "mockall_derive only supports structs defined in the current module");
-                return;
-            }
-            let ident = &mut type_path.path.segments.last_mut().unwrap().ident;
-            *ident = gen_mock_ident(ident)
+            let (ident, arguments) = find_ident_from_path(&type_path.path);
+            type_path.path = Path::from(PathSegment {
+                ident: gen_mock_ident(&ident),
+                arguments
+            });
+            Ok(())
         },
-        x => {
-            compile_error(x.span(),
-                "mockall_derive only supports mocking traits and structs");
-        }
-    };
+        x => Err(Error::new(x.span(),
+                "mockall_derive only supports mocking traits and structs"))
+    }
 }
 
 /// Performs transformations on the ItemImpl to make it mockable
 fn mockable_item_impl(mut impl_: ItemImpl, name: &Ident, generics: &Generics)
-    -> ItemImpl
+    -> Result<ItemImpl>
 {
-    mock_ident_in_type(&mut impl_.self_ty);
+    let mut errors = Errors::default();
+    errors.ok(mock_ident_in_type(&mut impl_.self_ty));
     for item in impl_.items.iter_mut() {
         if let ImplItem::Fn(ref mut iim) = item {
-            mockable_method(iim, name, generics);
+            errors.ok(mockable_sig(&mut iim.sig, name, generics));
         }
     }
-    impl_
+    errors.finish(impl_)
 }
 
-/// Performs transformations on the method to make it mockable
-fn mockable_method(meth: &mut ImplItemFn, name: &Ident, generics: &Generics)
+/// Performs transformations on a method's signature to make it mockable
+fn mockable_sig(sig: &mut Signature, name: &Ident, generics: &Generics)
+    -> Result<()>
 {
-    demutify(&mut meth.sig.inputs);
-    deselfify_args(&mut meth.sig.inputs, name, generics);
-    add_lifetime_parameters(&mut meth.sig);
-    deimplify(&mut meth.sig.output);
-    dewhereselfify(&mut meth.sig.generics);
-    if let ReturnType::Type(_, ty) = &mut meth.sig.output {
-        deselfify(ty, name, generics);
-        deanonymize(ty);
+    let mut errors = Errors::default();
+    errors.ok(demutify(&mut sig.inputs));
+    errors.ok(deselfify_args(&mut sig.inputs, name, generics));
+    errors.ok(add_lifetime_parameters(sig));
+    deimplify(&mut sig.output);
+    dewhereselfify(&mut sig.generics);
+    if let ReturnType::Type(_, ty) = &mut sig.output {
+        errors.ok(deselfify(ty, name, generics));
+        errors.ok(deanonymize(ty));
     }
-    sanity_check_sig(&meth.sig);
+    errors.ok(sanity_check_sig(sig));
+    errors.finish(())
 }
 
-/// Performs transformations on the method to make it mockable
-fn mockable_trait_method(
-    meth: &mut TraitItemFn,
-    name: &Ident,
-    generics: &Generics)
-{
-    demutify(&mut meth.sig.inputs);
-    deselfify_args(&mut meth.sig.inputs, name, generics);
-    add_lifetime_parameters(&mut meth.sig);
-    deimplify(&mut meth.sig.output);
-    dewhereselfify(&mut meth.sig.generics);
-    if let ReturnType::Type(_, ty) = &mut meth.sig.output {
-        deselfify(ty, name, generics);
-        deanonymize(ty);
+/// Generates a helper trait holding copies of a trait's default method
+/// implementations, so the mock struct can still call them after overriding
+/// the originals.
+///
+/// Each method is renamed to avoid ambiguity with the original trait's
+/// methods.  Async methods and methods returning `impl Trait` aren't copied,
+/// because the mock struct's versions have different signatures.
+fn default_trait(trait_: &ItemTrait) -> Option<Box<ItemTrait>> {
+    let items = trait_.items.iter()
+    .filter_map(|ti| {
+        match ti {
+            TraitItem::Fn(tif) if tif.default.is_some() &&
+                tif.sig.asyncness.is_none() &&
+                !matches!(&tif.sig.output,
+                          ReturnType::Type(_, ty)
+                            if matches!(**ty, Type::ImplTrait(_))) =>
+            {
+                let mut tif = tif.clone();
+                tif.sig.ident = gen_default_fn_ident(&tif.sig.ident);
+                Some(tif)
+            },
+            _ => None
+        }
+    }).collect::<Vec<_>>();
+    if items.is_empty() {
+        return None;
     }
-    sanity_check_sig(&meth.sig);
+    let ident = gen_default_trait_ident(&trait_.ident);
+    let trait_ident = &trait_.ident;
+    let generics = &trait_.generics;
+    let (_, tg, _) = generics.split_for_impl();
+    let wc = &generics.where_clause;
+    Some(Box::new(parse2(quote!(
+        #[allow(non_camel_case_types)]
+        trait #ident #generics: #trait_ident #tg #wc {
+            #(#items)*
+        }
+    )).unwrap()))
 }
 
 /// Generates a mockable item impl from a trait method definition
 fn mockable_trait(trait_: ItemTrait, name: &Ident, generics: &Generics)
-    -> ItemImpl
+    -> Result<ItemImpl>
 {
+    let mut errors = Errors::default();
     let items = trait_.items.into_iter()
-    .map(|ti| {
-        match ti {
+    .filter_map(|ti| {
+        let r = match ti {
             TraitItem::Fn(mut tif) => {
-                mockable_trait_method(&mut tif, name, generics);
-                ImplItem::Fn(tif2iif(tif, &Visibility::Inherited))
+                mockable_sig(&mut tif.sig, name, generics)
+                .map(|_| ImplItem::Fn(tif2iif(tif, &Visibility::Inherited)))
             },
             TraitItem::Const(tic) => {
-                ImplItem::Const(tic2iic(tic, &Visibility::Inherited))
+                tic2iic(tic, &Visibility::Inherited).map(ImplItem::Const)
             },
             TraitItem::Type(tit) => {
-                ImplItem::Type(tit2iit(tit, &Visibility::Inherited))
+                tit2iit(tit, &Visibility::Inherited).map(ImplItem::Type)
             },
-            _ => {
-                compile_error(ti.span(), "Unsupported in this context");
-                ImplItem::Verbatim(TokenStream::new())
-            }
-        }
+            _ => Err(Error::new(ti.span(), "Unsupported in this context"))
+        };
+        errors.ok(r)
     }).collect::<Vec<_>>();
     let mut trait_path = Path::from(trait_.ident);
     let mut struct_path = Path::from(name.clone());
@@ -226,7 +260,7 @@ fn mockable_trait(trait_: ItemTrait, name: &Ident, generics: &Generics)
         qself: None,
         path: struct_path,
     }));
-    ItemImpl {
+    errors.finish(ItemImpl {
         attrs: trait_.attrs,
         defaultness: None,
         unsafety: trait_.unsafety,
@@ -236,10 +270,11 @@ fn mockable_trait(trait_: ItemTrait, name: &Ident, generics: &Generics)
         self_ty,
         brace_token: trait_.brace_token,
         items
-    }
+    })
 }
 
-fn sanity_check_sig(sig: &Signature) {
+fn sanity_check_sig(sig: &Signature) -> Result<()> {
+    let mut errors = Errors::default();
     for arg in sig.inputs.iter() {
         if let FnArg::Typed(pt) = arg {
             if let Type::ImplTrait(it) = pt.ty.as_ref() {
@@ -248,21 +283,21 @@ fn sanity_check_sig(sig: &Signature) {
                     "Mockall does not support \"impl trait\" in argument position.  Use \"T: {}\" instead",
                     quote!(#bounds)
                 );
-                compile_error(it.span(), &s);
+                errors.push(Error::new(it.span(), s));
             }
         }
     }
+    errors.finish(())
 }
 
 /// Converts a TraitItemConst into an ImplItemConst
-fn tic2iic(tic: TraitItemConst, vis: &syn::Visibility) -> ImplItemConst {
+fn tic2iic(tic: TraitItemConst, vis: &syn::Visibility)
+    -> Result<ImplItemConst>
+{
     let span = tic.span();
-    let (eq_token, expr) = tic.default.unwrap_or_else(|| {
-        compile_error(span,
-            "Mocked associated consts must have a default implementation");
-        (<Token![=]>::default(), Expr::Verbatim(TokenStream::new()))
-    });
-    ImplItemConst {
+    let (eq_token, expr) = tic.default.ok_or_else(|| Error::new(span,
+        "Mocked associated consts must have a default implementation"))?;
+    Ok(ImplItemConst {
         attrs: tic.attrs,
         vis: vis.clone(),
         defaultness: None,
@@ -274,7 +309,7 @@ fn tic2iic(tic: TraitItemConst, vis: &syn::Visibility) -> ImplItemConst {
         eq_token,
         expr,
         semi_token: tic.semi_token
-    }
+    })
 }
 
 /// Converts a TraitItemFn into an ImplItemFn
@@ -295,14 +330,11 @@ fn tif2iif(m: syn::TraitItemFn, vis: &syn::Visibility)
 }
 
 /// Converts a TraitItemType into an ImplItemType
-fn tit2iit(tit: TraitItemType, vis: &Visibility) -> ImplItemType {
+fn tit2iit(tit: TraitItemType, vis: &Visibility) -> Result<ImplItemType> {
     let span = tit.span();
-    let (eq_token, ty) = tit.default.unwrap_or_else(|| {
-        compile_error(span,
-            "associated types in mock! must be fully specified");
-        (token::Eq::default(), Type::Verbatim(TokenStream::new()))
-    });
-    ImplItemType {
+    let (eq_token, ty) = tit.default.ok_or_else(|| Error::new(span,
+        "associated types in mock! must be fully specified"))?;
+    Ok(ImplItemType {
         attrs: tit.attrs,
         vis: vis.clone(),
         defaultness: None,
@@ -312,7 +344,7 @@ fn tit2iit(tit: TraitItemType, vis: &Visibility) -> ImplItemType {
         eq_token,
         ty,
         semi_token: tit.semi_token,
-    }
+    })
 }
 
 /// Like a TraitItemFn, but with a visibility
@@ -334,12 +366,17 @@ impl Parse for TraitItemVFn {
 pub(crate) struct MockableStruct {
     pub attrs: Vec<Attribute>,
     pub consts: Vec<ImplItemConst>,
+    /// Helper trait holding the default method implementations of the
+    /// mocked trait, if any
+    pub defaults: Option<Box<ItemTrait>>,
     pub generics: Generics,
     /// Inherent methods of the mockable struct
     pub methods: Vec<ImplItemFn>,
     pub name: Ident,
     pub vis: Visibility,
     pub impls: Vec<ItemImpl>,
+    /// Are static methods' expectations stored per thread?
+    pub thread_local: bool,
 }
 
 impl MockableStruct {
@@ -361,9 +398,12 @@ impl MockableStruct {
     }
 }
 
-impl From<(Attrs, ItemTrait)> for MockableStruct {
-    fn from((attrs, item_trait): (Attrs, ItemTrait)) -> MockableStruct {
-        let trait_ = attrs.substitute_trait(&item_trait);
+impl TryFrom<(Attrs, ItemTrait)> for MockableStruct {
+    type Error = Error;
+
+    fn try_from((attrs, item_trait): (Attrs, ItemTrait)) -> Result<Self> {
+        let trait_ = attrs.substitute_trait(&item_trait)?;
+        let thread_local = attrs.thread_local;
         // Strip "must_use" from a trait definition.  For traits, the "must_use"
         // should apply only when the trait is used like "impl Trait" or "dyn
         // Trait".  So it shouldn't necessarily affect the mock struct that
@@ -377,32 +417,37 @@ impl From<(Attrs, ItemTrait)> for MockableStruct {
         let vis = trait_.vis.clone();
         let name = gen_mock_ident(&trait_.ident);
         let generics = trait_.generics.clone();
-        let impls = vec![mockable_trait(trait_, &name, &generics)];
-        MockableStruct {
+        let defaults = default_trait(&item_trait);
+        let impls = vec![mockable_trait(trait_, &name, &generics)?];
+        Ok(MockableStruct {
             attrs,
             consts: Vec::new(),
+            defaults,
             vis,
             name,
             generics,
             methods: Vec::new(),
-            impls
-        }
+            impls,
+            thread_local
+        })
     }
 }
 
-impl From<ItemImpl> for MockableStruct {
-    fn from(mut item_impl: ItemImpl) -> MockableStruct {
+impl TryFrom<ItemImpl> for MockableStruct {
+    type Error = Error;
+
+    fn try_from(mut item_impl: ItemImpl) -> Result<Self> {
         let name = match &*item_impl.self_ty {
             Type::Path(type_path) => {
                 let n = find_ident_from_path(&type_path.path).0;
                 gen_mock_ident(&n)
             },
             x => {
-                compile_error(x.span(),
-                    "mockall_derive only supports mocking traits and structs");
-                Ident::new("", Span::call_site())
+                return Err(Error::new(x.span(),
+                    "mockall_derive only supports mocking traits and structs"));
             }
         };
+        let mut errors = Errors::default();
         let mut attrs = item_impl.attrs.clone();
         attrs.push(derive_debug());
         let mut consts = Vec::new();
@@ -412,7 +457,7 @@ impl From<ItemImpl> for MockableStruct {
         let mut impls = Vec::new();
         if let Some((bang, _path, _)) = &item_impl.trait_ {
             if bang.is_some() {
-                compile_error(bang.span(), "Unsupported by automock");
+                errors.push(Error::new(bang.span(), "Unsupported by automock"));
             }
 
             // Substitute any associated types in this ItemImpl.
@@ -428,34 +473,43 @@ impl From<ItemImpl> for MockableStruct {
                     ImplItem::Type(ty) => {
                         attrs.attrs.insert(ty.ident.clone(), ty.ty.clone());
                     },
-                    x => compile_error(x.span(), "Unsupported by automock")
+                    x => errors.push(Error::new(x.span(),
+                        "Unsupported by automock"))
                 }
             }
-            attrs.substitute_item_impl(&mut item_impl);
-            impls.push(mockable_item_impl(item_impl, &name, &generics));
+            if errors.ok(attrs.substitute_item_impl(&mut item_impl)).is_some()
+            {
+                let r = mockable_item_impl(item_impl, &name, &generics);
+                impls.extend(errors.ok(r));
+            }
         } else {
             for item in item_impl.items.into_iter() {
                 match item {
                     ImplItem::Fn(mut meth) => {
-                        mockable_method(&mut meth, &name, &item_impl.generics);
-                        methods.push(meth)
+                        let r = mockable_sig(&mut meth.sig, &name,
+                                             &item_impl.generics);
+                        if errors.ok(r).is_some() {
+                            methods.push(meth)
+                        }
                     },
                     ImplItem::Const(iic) => consts.push(iic),
                     // Rust doesn't allow types in an inherent impl
-                    x => compile_error(x.span(),
-                        "Unsupported by Mockall in this context"),
+                    x => errors.push(Error::new(x.span(),
+                        "Unsupported by Mockall in this context")),
                 }
             }
         };
-        MockableStruct {
+        errors.finish(MockableStruct {
             attrs,
             consts,
+            defaults: None,
             generics,
             methods,
             name,
             vis,
             impls,
-        }
+            thread_local: false
+        })
     }
 }
 
@@ -478,7 +532,7 @@ impl Parse for MockableStruct {
                 ImplItem::Verbatim(ts) => {
                     let tivf: TraitItemVFn = parse2(ts)?;
                     let mut iim = tif2iif(tivf.tif, &tivf.vis);
-                    mockable_method(&mut iim, &name, &generics);
+                    mockable_sig(&mut iim.sig, &name, &generics)?;
                     methods.push(iim);
                 }
                 ImplItem::Const(iic) => consts.push(iic),
@@ -497,12 +551,12 @@ impl Parse for MockableStruct {
                         // Convert any methods that syn couldn't parse as
                         // ImplItemFn.
                         if let ImplItem::Verbatim(ts) = item {
-                            let tif: TraitItemFn = parse2(ts.clone()).unwrap();
+                            let tif: TraitItemFn = parse2(ts.clone())?;
                             let iim = tif2iif(tif, &Visibility::Inherited);
                             *item = ImplItem::Fn(iim);
                         }
                     }
-                    impls.push(mockable_item_impl(ii, &name, &generics));
+                    impls.push(mockable_item_impl(ii, &name, &generics)?);
                 }
                 _ => return Err(input.error("Unsupported in this context")),
             }
@@ -512,11 +566,13 @@ impl Parse for MockableStruct {
             MockableStruct {
                 attrs,
                 consts,
+                defaults: None,
                 generics,
                 methods,
                 name,
                 vis,
-                impls
+                impls,
+                thread_local: false
             }
         )
     }
@@ -534,7 +590,7 @@ mod add_lifetime_parameters {
         let mut meth: TraitItemFn = parse2(quote!(
             fn foo(&self, x: [&dyn T; 1]);
         )).unwrap();
-        add_lifetime_parameters(&mut meth.sig);
+        add_lifetime_parameters(&mut meth.sig).unwrap();
         assert_eq!(
             quote!(fn foo<'__mockall_x>(&self, x: [&(dyn T + '__mockall_x); 1]);)
                 .to_string(),
@@ -547,7 +603,7 @@ mod add_lifetime_parameters {
         let mut meth: TraitItemFn = parse2(quote!(
             fn foo(&self, x: fn(&dyn T));
         )).unwrap();
-        add_lifetime_parameters(&mut meth.sig);
+        add_lifetime_parameters(&mut meth.sig).unwrap();
         assert_eq!(
             quote!(fn foo(&self, x: fn(&dyn T));).to_string(),
             quote!(#meth).to_string()
@@ -559,7 +615,7 @@ mod add_lifetime_parameters {
         let mut meth: TraitItemFn = parse2(quote!(
             fn foo(&self, x: &dyn T);
         )).unwrap();
-        add_lifetime_parameters(&mut meth.sig);
+        add_lifetime_parameters(&mut meth.sig).unwrap();
         assert_eq!(
             quote!(fn foo<'__mockall_x>(&self, x: &(dyn T + '__mockall_x));)
                 .to_string(),
@@ -572,7 +628,7 @@ mod add_lifetime_parameters {
         let mut meth: TraitItemFn = parse2(quote!(
             fn foo(&self, x: &[&dyn T]);
         )).unwrap();
-        add_lifetime_parameters(&mut meth.sig);
+        add_lifetime_parameters(&mut meth.sig).unwrap();
         assert_eq!(
             quote!(fn foo<'__mockall_x>(&self, x: &[&(dyn T + '__mockall_x)]);)
                 .to_string(),
@@ -585,7 +641,7 @@ mod add_lifetime_parameters {
         let mut meth: TraitItemFn = parse2(quote!(
             fn foo(&self, x: (&dyn T, u32));
         )).unwrap();
-        add_lifetime_parameters(&mut meth.sig);
+        add_lifetime_parameters(&mut meth.sig).unwrap();
         assert_eq!(
             quote!(fn foo<'__mockall_x>(&self, x: (&(dyn T + '__mockall_x), u32));)
                 .to_string(),
@@ -598,7 +654,7 @@ mod add_lifetime_parameters {
         let mut meth: TraitItemFn = parse2(quote!(
             fn foo(&self, x: &(dyn T + '_));
         )).unwrap();
-        add_lifetime_parameters(&mut meth.sig);
+        add_lifetime_parameters(&mut meth.sig).unwrap();
         assert_eq!(
             quote!(fn foo(&self, x: &(dyn T + '_));).to_string(),
             quote!(#meth).to_string()
@@ -610,7 +666,7 @@ mod add_lifetime_parameters {
         let mut meth: TraitItemFn = parse2(quote!(
             fn foo(&self, x: &(dyn T));
         )).unwrap();
-        add_lifetime_parameters(&mut meth.sig);
+        add_lifetime_parameters(&mut meth.sig).unwrap();
         assert_eq!(
             quote!(fn foo<'__mockall_x>(&self, x: &(dyn T + '__mockall_x));)
                 .to_string(),
@@ -623,7 +679,7 @@ mod add_lifetime_parameters {
         let mut meth: TraitItemFn = parse2(quote!(
             fn foo<'a>(&self, x: &(dyn T + 'a));
         )).unwrap();
-        add_lifetime_parameters(&mut meth.sig);
+        add_lifetime_parameters(&mut meth.sig).unwrap();
         assert_eq!(
             quote!(fn foo<'a>(&self, x: &(dyn T + 'a));).to_string(),
             quote!(#meth).to_string()
@@ -635,7 +691,7 @@ mod add_lifetime_parameters {
         let mut meth: TraitItemFn = parse2(quote!(
             fn foo(&self, x: &(dyn T + 'static));
         )).unwrap();
-        add_lifetime_parameters(&mut meth.sig);
+        add_lifetime_parameters(&mut meth.sig).unwrap();
         assert_eq!(
             quote!(fn foo(&self, x: &(dyn T + 'static));).to_string(),
             quote!(#meth).to_string()
@@ -648,12 +704,12 @@ mod sanity_check_sig {
     use super::*;
 
     #[test]
-    #[should_panic(expected = "Mockall does not support \"impl trait\" in argument position.  Use \"T: SomeTrait\" instead.")]
     fn impl_trait() {
         let meth: ImplItemFn = parse2(quote!(
             fn foo(&self, x: impl SomeTrait) {}
         )).unwrap();
-        sanity_check_sig(&meth.sig);
+        assert_eq!(sanity_check_sig(&meth.sig).unwrap_err().to_string(),
+            "Mockall does not support \"impl trait\" in argument position.  Use \"T: SomeTrait\" instead");
     }
 }
 }
@@ -7,48 +7,32 @@
 //! However, it is defined in its own crate so that the bulk of Mockall can
 //! remain a dev-dependency, instead of a regular dependency.
 
-#![cfg_attr(feature = "nightly", feature(proc_macro_diagnostic))]
 #![cfg_attr(test, deny(warnings))]
 extern crate proc_macro;
 
-use cfg_if::cfg_if;
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use syn::{
     *,
+    ext::IdentExt,
     spanned::Spanned
 };
 
-cfg_if! {
-    // proc-macro2's Span::unstable method requires the nightly feature, and it
-    // doesn't work in test mode.
-    // https://github.com/alexcrichton/proc-macro2/issues/159
-    if #[cfg(all(feature = "nightly", not(test)))] {
-        fn compile_error(span: Span, msg: &'static str) {
-            span.unstable()
-                .error(msg)
-                .emit();
-        }
-    } else {
-        fn compile_error(_span: Span, msg: &str) {
-            panic!("{msg}.  More information may be available when mockall_double is built with the \"nightly\" feature.");
-        }
-    }
-}
-
 fn do_double(_attrs: TokenStream, input: TokenStream) -> TokenStream {
     let mut item: Item = match parse2(input.clone()) {
         Ok(u) => u,
         Err(e) => return e.to_compile_error()
     };
-    match &mut item {
+    let r = match &mut item {
         Item::Use(use_stmt) => mock_itemuse(use_stmt),
         Item::Type(item_type) => mock_itemtype(item_type),
-        _ => {
-            compile_error(item.span(),
-                "Only use statements and type aliases may be doubled");
-        }
+        _ => Err(Error::new(item.span(),
+                "Only use statements and type aliases may be doubled"))
     };
+    if let Err(e) = r {
+        let e = e.to_compile_error();
+        return quote!(#input #e);
+    }
     quote!(
         #[cfg(not(test))]
         #input
@@ -160,65 +144,80 @@ pub fn double(attrs: proc_macro::TokenStream, input: proc_macro::TokenStream)
     do_double(attrs.into(), input.into()).into()
 }
 
-fn mock_itemtype(orig: &mut ItemType) {
+fn mock_itemtype(orig: &mut ItemType) -> Result<()> {
     match &mut *orig.ty {
         Type::Path(tp) => {
             let ident = &tp.path.segments.last_mut().unwrap().ident;
-            tp.path.segments.last_mut().unwrap().ident = mock_ident(ident);
+            tp.path.segments.last_mut().unwrap().ident = mock_ident(ident)?;
+            Ok(())
         }
-        x => compile_error(x.span(), "Only path types may be doubled")
+        x => Err(Error::new(x.span(), "Only path types may be doubled"))
     }
 }
 
-fn mock_itemuse(orig: &mut ItemUse) {
+fn mock_itemuse(orig: &mut ItemUse) -> Result<()> {
     if let UseTree::Name(un) = &orig.tree {
-        compile_error(un.span(),
-            "Cannot double types in the current module.  Use a submodule (use foo::Foo) or a rename (use Foo as Bar)");
+        Err(Error::new(un.span(),
+            "Cannot double types in the current module.  Use a submodule (use foo::Foo) or a rename (use Foo as Bar)"))
     } else {
         mock_usetree(&mut orig.tree)
     }
 }
 
-fn mock_ident(i: &Ident) -> Ident {
-    let is_type = format!("{i}")
+fn mock_ident(i: &Ident) -> Result<Ident> {
+    let is_type = i.unraw()
+        .to_string()
         .chars()
         .next()
-        .expect("zero-length ident?")
+        .ok_or_else(|| Error::new(i.span(), "Cannot double an empty name"))?
         .is_uppercase();
     if is_type {
         // probably a Type
-        format_ident!("Mock{}", i)
+        Ok(format_ident!("Mock{}", i))
     } else {
         // probably a module
-        format_ident!("mock_{}", i)
+        Ok(format_ident!("mock_{}", i))
     }
 }
 
-fn mock_usetree(mut orig: &mut UseTree) {
+/// Replace every name in the tree with its mock counterpart
+///
+/// Reports every unsupported import in the tree, not just the first.
+fn mock_usetree(mut orig: &mut UseTree) -> Result<()> {
     match &mut orig {
         UseTree::Glob(star) => {
-            compile_error(star.span(),
-                "Cannot double glob imports.  Import by fully qualified name instead.");
+            return Err(Error::new(star.span(),
+                "Cannot double glob imports.  Import by fully qualified name instead."));
         },
         UseTree::Group(ug) => {
+            let mut errors: Option<Error> = None;
             for ut in ug.items.iter_mut() {
-                mock_usetree(ut);
+                if let Err(e) = mock_usetree(ut) {
+                    match &mut errors {
+                        Some(errs) => errs.combine(e),
+                        None => errors = Some(e)
+                    }
+                }
+            }
+            if let Some(e) = errors {
+                return Err(e);
             }
         },
         UseTree::Name(un) => {
             *orig = UseTree::Rename(UseRename {
-                ident: mock_ident(&un.ident),
+                ident: mock_ident(&un.ident)?,
                 as_token: <Token![as]>::default(),
                 rename: un.ident.clone()
             });
         },
         UseTree::Path(up) => {
-            mock_usetree(up.tree.as_mut());
+            mock_usetree(up.tree.as_mut())?;
         },
         UseTree::Rename(ur) => {
-            ur.ident = mock_ident(&ur.ident)
+            ur.ident = mock_ident(&ur.ident)?
         },
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -242,11 +241,36 @@ mod double {
         assert_eq!(output, expected);
     }
 
+    /// Check that `code` fails with the expected error messages
+    fn errors(code: &str, expected: &[&str]) {
+        let attrs_ts = TokenStream::new();
+        let code_ts = TokenStream::from_str(code).unwrap();
+        let mut output = do_double(attrs_ts, code_ts).to_string();
+        // The original item is still emitted
+        assert!(output.starts_with(&TokenStream::from_str(code).unwrap()
+                                   .to_string()));
+        for msg in expected {
+            let e = quote!(::core::compile_error! { #msg }).to_string();
+            assert!(output.contains(&e), "{output:?} does not contain {e:?}");
+            output = output.replacen(&e, "", 1);
+        }
+        assert!(!output.contains("compile_error"), "extra errors: {output}");
+    }
+
     #[test]
-    #[should_panic(expected = "Cannot double glob")]
     fn glob() {
-        let code = "use foo::*;";
-        cmp("", code, "");
+        errors("use foo::*;", &[
+            "Cannot double glob imports.  Import by fully qualified name instead."
+        ]);
+    }
+
+    /// Every bad import in a group is reported
+    #[test]
+    fn group_errors() {
+        errors("use foo::{bar::*, Baz, bean::*};", &[
+            "Cannot double glob imports.  Import by fully qualified name instead.",
+            "Cannot double glob imports.  Import by fully qualified name instead."
+        ]);
     }
 
     #[test]
@@ -285,10 +309,10 @@ mod double {
     }
 
     #[test]
-    #[should_panic(expected = "Cannot double types in the current module")]
     fn name() {
-        let code = "use Foo;";
-        cmp("", code, "");
+        errors("use Foo;", &[
+            "Cannot double types in the current module.  Use a submodule (use foo::Foo) or a rename (use Foo as Bar)"
+        ]);
     }
 
     #[test]
@@ -340,10 +364,10 @@ mod double {
     }
 
     #[test]
-    #[should_panic(expected = "Only use statements and type aliases")]
     fn undoubleable() {
-        let code = "struct Foo{}";
-        cmp("", code, "");
+        errors("struct Foo{}", &[
+            "Only use statements and type aliases may be doubled"
+        ]);
     }
 }
 }